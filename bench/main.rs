@@ -1,38 +1,98 @@
 mod utils;
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use sahomedb::collection::{Collection, Config};
+use sahomedb::collection::{Collection, Config, IndexType, Record};
 use sahomedb::vector::Vector;
 use utils::*;
 
-fn build_collection(path: &str) -> Collection {
-    let records = get_records(path).unwrap();
-    let config = Config::default();
-    Collection::build(&config, &records).unwrap()
-}
+/// Index types to compare with their names.
+const INDEXES: [(&str, IndexType); 3] = [
+    ("HNSW", IndexType::Hnsw),
+    ("IVF", IndexType::Ivf { lists: 100, probes: 8 }),
+    ("Flat", IndexType::Flat),
+];
 
-fn bench_search_collection(criterion: &mut Criterion) {
-    let id = "Search collection";
+fn build_collection(records: &[Record], index: IndexType) -> Collection {
+    let config = Config { index, ..Default::default() };
+    Collection::build(&config, records).unwrap()
+}
 
+fn get_dataset() -> (Vec<Record>, Vec<Vector>) {
     // Download the dataset.
     download_siftsmall().unwrap();
 
-    // Load the query data.
+    let base_path = "data/siftsmall/siftsmall_base.fvecs";
+    let records = get_records(base_path).unwrap();
+
     let query_path = "data/siftsmall/siftsmall_query.fvecs";
     let query_data = read_vectors(query_path).unwrap();
-    let query: &Vector = &query_data[0].clone().into();
+    let queries = query_data.into_iter().map(Vector::from).collect();
 
-    // Create the collection.
-    let base_path = "data/siftsmall/siftsmall_base.fvecs";
-    let collection = build_collection(base_path);
+    (records, queries)
+}
+
+fn bench_build_collection(criterion: &mut Criterion) {
+    let (records, _) = get_dataset();
+
+    let mut group = criterion.benchmark_group("Build collection");
+    group.sample_size(10);
+
+    for (name, index) in INDEXES {
+        let routine = || {
+            black_box(build_collection(&records, index));
+        };
+
+        group.bench_function(name, |bencher| bencher.iter(routine));
+    }
+
+    group.finish();
+}
+
+fn bench_search_collection(criterion: &mut Criterion) {
+    let (records, queries) = get_dataset();
+    let query = &queries[0];
+
+    let mut group = criterion.benchmark_group("Search collection");
+
+    for (name, index) in INDEXES {
+        let collection = build_collection(&records, index);
+
+        // Criterion only measures the speed so the recall is printed.
+        let report = collection.evaluate_recall(&queries, 10).unwrap();
+        println!("{} recall@10: {:.3}", name, report.recall);
+
+        // Benchmark the search speed.
+        let routine = || {
+            black_box(collection.search(query, 10).unwrap());
+        };
+
+        group.bench_function(name, |bencher| bencher.iter(routine));
+    }
+
+    group.finish();
+}
+
+fn bench_vector_distance(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("Vector distance");
+
+    for dimension in [128, 384, 768, 1536] {
+        let a = Vector::random(dimension);
+        let b = Vector::random(dimension);
+        let routine = || {
+            black_box(black_box(&a).distance(black_box(&b)));
+        };
 
-    // Benchmark the search speed.
-    let routine = || {
-        black_box(collection.search(query, 10).unwrap());
-    };
+        let id = dimension.to_string();
+        group.bench_function(id, |bencher| bencher.iter(routine));
+    }
 
-    criterion.bench_function(id, |bencher| bencher.iter(routine));
+    group.finish();
 }
 
-criterion_group!(bench, bench_search_collection);
+criterion_group!(
+    bench,
+    bench_vector_distance,
+    bench_build_collection,
+    bench_search_collection
+);
 criterion_main!(bench);
@@ -0,0 +1,151 @@
+use sahomedb::collection::*;
+use sahomedb::vector::Vector;
+use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, ErrorKind, Read};
+
+const USAGE: &str = "Usage: evaluate-recall <base.fvecs> [options]
+
+Options:
+  --queries <path>          Query vectors (.fvecs). Samples the base
+                            vectors if not provided.
+  --sample <n>              Number of base vectors to sample. Default: 100
+  --k <n>                   Number of neighbors to compare. Default: 10
+  --ef-search <list>        Comma-separated values to evaluate.
+                            Default: 15,32,64,128
+  --ef-construction <n>     Default: 40";
+
+/// Options of the evaluation parsed from the arguments.
+struct Options {
+    base: String,
+    queries: Option<String>,
+    sample: usize,
+    k: usize,
+    ef_search: Vec<usize>,
+    ef_construction: usize,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let options = match parse_args(env::args().skip(1).collect()) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            std::process::exit(1);
+        }
+    };
+
+    // Build the collection where the ID of the record is its index.
+    let vectors = read_fvecs(&options.base)?;
+    let records: Vec<Record> = vectors
+        .into_iter()
+        .enumerate()
+        .map(|(id, vector)| Record::new(&vector.into(), &id.into()))
+        .collect();
+
+    let ef_construction = options.ef_construction;
+    let config = Config { ef_construction, ..Default::default() };
+    let mut collection = Collection::build(&config, &records)?;
+
+    let queries: Vec<Vector> = match &options.queries {
+        Some(path) => read_fvecs(path)?.into_iter().map(Vector::from).collect(),
+        None => collection.sample_vectors(options.sample),
+    };
+
+    println!("Records: {}", collection.len());
+    println!("Queries: {}", queries.len());
+    println!("ef_construction: {}", ef_construction);
+    println!();
+
+    let k = options.k;
+    let header = format!("recall@{}", k);
+    println!(
+        "{:>10} {:>10} {:>10} {:>10} {:>10} {:>10}",
+        "ef_search", header, "qps", "p50 (us)", "p95 (us)", "p99 (us)"
+    );
+
+    for ef in options.ef_search {
+        collection.config.ef_search = ef;
+        let report = collection.evaluate_recall(&queries, k)?;
+        println!(
+            "{:>10} {:>10.4} {:>10.0} {:>10} {:>10} {:>10}",
+            ef,
+            report.recall,
+            report.qps,
+            report.p50.as_micros(),
+            report.p95.as_micros(),
+            report.p99.as_micros(),
+        );
+    }
+
+    Ok(())
+}
+
+fn parse_args(args: Vec<String>) -> Result<Options, Box<dyn Error>> {
+    let mut args = args.into_iter();
+    let base = match args.next() {
+        Some(base) if !base.starts_with("--") => base,
+        _ => return Err("The base vectors file is required.".into()),
+    };
+
+    let mut options = Options {
+        base,
+        queries: None,
+        sample: 100,
+        k: 10,
+        ef_search: vec![15, 32, 64, 128],
+        ef_construction: 40,
+    };
+
+    while let Some(flag) = args.next() {
+        let value = match args.next() {
+            Some(value) => value,
+            None => return Err(format!("Missing value of {}.", flag).into()),
+        };
+
+        match flag.as_str() {
+            "--queries" => options.queries = Some(value),
+            "--sample" => options.sample = value.parse()?,
+            "--k" => options.k = value.parse()?,
+            "--ef-construction" => options.ef_construction = value.parse()?,
+            "--ef-search" => {
+                let values = value.split(',').map(|ef| ef.trim().parse());
+                options.ef_search = values.collect::<Result<_, _>>()?;
+            }
+            _ => return Err(format!("Unknown option {}.", flag).into()),
+        }
+    }
+
+    Ok(options)
+}
+
+/// Reads the vectors from a `.fvecs` file where each vector is
+/// prefixed by its dimension as a little-endian 32-bit integer.
+fn read_fvecs(path: &str) -> Result<Vec<Vec<f32>>, Box<dyn Error>> {
+    if !path.ends_with(".fvecs") {
+        return Err("Invalid file extension.".into());
+    }
+
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut vectors = vec![];
+    let mut word = [0; 4];
+
+    loop {
+        match reader.read_exact(&mut word) {
+            Ok(()) => {}
+            Err(error) if error.kind() == ErrorKind::UnexpectedEof => break,
+            Err(error) => return Err(error.into()),
+        }
+
+        let dimension = i32::from_le_bytes(word) as usize;
+        let mut vector = Vec::with_capacity(dimension);
+        for _ in 0..dimension {
+            reader.read_exact(&mut word)?;
+            vector.push(f32::from_le_bytes(word));
+        }
+
+        vectors.push(vector);
+    }
+
+    Ok(vectors)
+}
@@ -0,0 +1,26 @@
+use sahomedb::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Article {
+    title: String,
+    views: u32,
+    tags: Vec<String>,
+}
+
+fn main() {
+    // Inserting a struct as the metadata of a record.
+    let article = Article {
+        title: "This is an example.".into(),
+        views: 10,
+        tags: vec!["example".into()],
+    };
+
+    let data = Metadata::from_serializable(&article).unwrap();
+    let vector = Vector::random(128);
+    let record = Record::new(&vector, &data);
+
+    // Extracting the struct back from the metadata.
+    let article: Article = record.data.deserialize_into().unwrap();
+    println!("{:?}", article);
+}
@@ -1,9 +1,53 @@
 use super::*;
 
+/// Tree storing the configuration of the collections.
+const CONFIG_TREE: &str = "config";
+/// Prefix of the trees storing the metadata of the collections.
+const DATA_TREE: &str = "data";
+/// Tree storing the names of the disk collections.
+const DISK_TREE: &str = "disk";
+/// Prefix of the trees storing the metadata indexes of the collections.
+const INDEX_TREE: &str = "index";
+/// Tree storing the IVF indexes of the collections.
+const IVF_TREE: &str = "ivf";
+/// Tree storing the named vector fields of the collections.
+const FIELDS_TREE: &str = "fields";
+/// Tree storing the information of the collections.
+const INFO_TREE: &str = "info";
+/// Tree storing the sparse vectors of the collections.
+const SPARSE_TREE: &str = "sparse";
+/// Tree storing the text indexes of the collections.
+const TEXT_TREE: &str = "text";
+/// Tree storing the vector arenas of the collections.
+const VECTORS_TREE: &str = "vectors";
+
 /// The database storing vector collections.
 pub struct Database {
     collections: Db,
     count: usize,
+    path: PathBuf,
+}
+
+/// The collection information stored alongside the collection.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CollectionInfo {
+    /// Number of vector records in the collection.
+    pub len: usize,
+    /// Vector dimension of the collection.
+    pub dimension: usize,
+    /// The collection configuration.
+    #[serde(with = "base_config")]
+    pub config: Config,
+}
+
+impl From<&Collection> for CollectionInfo {
+    fn from(collection: &Collection) -> Self {
+        Self {
+            len: collection.len(),
+            dimension: collection.dimension(),
+            config: collection.config.clone(),
+        }
+    }
 }
 
 impl Database {
@@ -20,7 +64,7 @@ impl Database {
         // with collection's Config.
         let config = sled::Config::new().path(path);
         let collections = config.open()?;
-        Ok(Self { collections, count: 0 })
+        Ok(Self { collections, count: 0, path: path.into() })
     }
 
     /// Opens existing or creates new database.
@@ -29,7 +73,7 @@ impl Database {
     pub fn open(path: &str) -> Result<Self, Box<dyn Error>> {
         let collections = sled::open(path)?;
         let count = collections.len();
-        Ok(Self { collections, count })
+        Ok(Self { collections, count, path: path.into() })
     }
 
     /// Creates a new collection in the database.
@@ -61,16 +105,53 @@ impl Database {
     }
 
     /// Gets a collection from the database.
+    ///
+    /// The metadata of the records is read from the database on demand
+    /// until the collection changes it. Before the stored metadata is
+    /// changed by saving another handle or deleting or renaming the
+    /// stored collection, the metadata is copied to this handle.
     /// * `name` - Name of the collection.
     pub fn get_collection(
         &self,
         name: &str,
     ) -> Result<Collection, Box<dyn Error>> {
         let value = self.collections.get(name)?;
-        match value {
-            Some(value) => Ok(bincode::deserialize(&value)?),
-            None => Err(err::COLLECTION_NOT_FOUND.into()),
+        let mut collection: Collection = match value {
+            Some(value) => bincode::deserialize(&value)?,
+            None => return Err(err::COLLECTION_NOT_FOUND.into()),
+        };
+
+        if let Some(config) = self.load_config(name)? {
+            collection.config = config;
         }
+
+        let vectors = self.collections.open_tree(VECTORS_TREE)?.get(name)?;
+        collection.load_vectors(vectors.as_deref())?;
+
+        // The metadata is loaded from its own tree when needed.
+        let data = self.collections.open_tree(data_tree_name(name))?;
+        collection.attach_data(data);
+
+        // The indexes are loaded before the replay to keep them in sync.
+        let index = self.collections.open_tree(index_tree_name(name))?;
+        collection.load_indexes(&index)?;
+        let text = self.collections.open_tree(TEXT_TREE)?.get(name)?;
+        collection.load_text_index(text.as_deref())?;
+        let sparse = self.collections.open_tree(SPARSE_TREE)?.get(name)?;
+        collection.load_sparse_index(sparse.as_deref())?;
+        let fields = self.collections.open_tree(FIELDS_TREE)?.get(name)?;
+        collection.load_fields(fields.as_deref())?;
+        let ivf = self.collections.open_tree(IVF_TREE)?.get(name)?;
+        collection.load_ivf_index(ivf.as_deref())?;
+
+        // Replay the changes since the last checkpoint and keep
+        // logging the new changes to the write-ahead log.
+        if let Some(wal) = self.open_wal(name)? {
+            wal.replay(&mut collection)?;
+            collection.attach_wal(wal);
+        }
+
+        Ok(collection)
     }
 
     /// Saves new or update existing collection to the database.
@@ -88,8 +169,17 @@ impl Database {
             new = true;
         }
 
-        let value = bincode::serialize(collection)?;
-        self.collections.insert(name, value)?;
+        // The stored collection supersedes the logged operations.
+        // The operations logged by another handle can't be cleared.
+        let wal = self.open_wal(name)?;
+        if let Some(wal) = &wal {
+            let owner = collection.wal().is_some_and(|own| own.owns(wal));
+            if wal.is_owned() && !owner {
+                return Err(err::WAL_IN_USE.into());
+            }
+        }
+
+        store_collection(&self.collections, name, collection, wal.as_ref())?;
 
         // If it's a new collection, update the count.
         if new {
@@ -105,11 +195,289 @@ impl Database {
         &mut self,
         name: &str,
     ) -> Result<(), Box<dyn Error>> {
-        self.collections.remove(name)?;
+        if self.collections.remove(name)?.is_none() {
+            return Err(err::COLLECTION_NOT_FOUND.into());
+        }
+
         self.count -= 1;
+        self.collections.open_tree(INFO_TREE)?.remove(name)?;
+        self.collections.open_tree(CONFIG_TREE)?.remove(name)?;
+        self.collections.open_tree(TEXT_TREE)?.remove(name)?;
+        self.collections.open_tree(SPARSE_TREE)?.remove(name)?;
+        self.collections.open_tree(FIELDS_TREE)?.remove(name)?;
+        self.collections.open_tree(IVF_TREE)?.remove(name)?;
+        self.collections.open_tree(VECTORS_TREE)?.remove(name)?;
+
+        // The loaded collections keep the metadata of the dropped tree.
+        let data = self.collections.open_tree(data_tree_name(name))?;
+        Collection::detach_data(&data)?;
+        self.collections.drop_tree(data_tree_name(name))?;
+        self.collections.drop_tree(index_tree_name(name))?;
+
+        // Remove the write-ahead log of the collection if any.
+        self.collections.open_tree(WAL_TREE)?.remove(name)?;
+        self.collections.drop_tree(WriteAheadLog::tree_name(name))?;
         Ok(())
     }
 
+    /// Returns the names of the collections in the database.
+    pub fn list_collections(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut names = Vec::with_capacity(self.count);
+        for key in self.collections.iter().keys() {
+            names.push(String::from_utf8(key?.to_vec())?);
+        }
+
+        Ok(names)
+    }
+
+    /// Returns the size, dimension, and configuration of a collection
+    /// as of its last save without loading the collection.
+    /// * `name` - Name of the collection.
+    pub fn collection_info(
+        &self,
+        name: &str,
+    ) -> Result<CollectionInfo, Box<dyn Error>> {
+        if let Some(value) = self.collections.open_tree(INFO_TREE)?.get(name)? {
+            let mut info: CollectionInfo = bincode::deserialize(&value)?;
+            if let Some(config) = self.load_config(name)? {
+                info.config = config;
+            }
+
+            return Ok(info);
+        }
+
+        // Collections saved before the info tree existed.
+        let collection = self.get_collection(name)?;
+        Ok(CollectionInfo::from(&collection))
+    }
+
+    /// Copies a collection including its pending logged operations.
+    /// * `source` - Name of the collection to copy.
+    /// * `target` - Name of the new collection.
+    pub fn copy_collection(
+        &mut self,
+        source: &str,
+        target: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let value = match self.collections.get(source)? {
+            Some(value) => value,
+            None => return Err(err::COLLECTION_NOT_FOUND.into()),
+        };
+
+        if self.collections.contains_key(target)? {
+            return Err(err::COLLECTION_EXISTS.into());
+        }
+
+        self.copy_tree(&data_tree_name(source), &data_tree_name(target))?;
+        self.copy_tree(&index_tree_name(source), &index_tree_name(target))?;
+
+        let trees = [
+            INFO_TREE,
+            CONFIG_TREE,
+            TEXT_TREE,
+            SPARSE_TREE,
+            FIELDS_TREE,
+            IVF_TREE,
+            VECTORS_TREE,
+        ];
+
+        for tree in trees {
+            let tree = self.collections.open_tree(tree)?;
+            if let Some(value) = tree.get(source)? {
+                tree.insert(target, value)?;
+            }
+        }
+
+        let wal = self.collections.open_tree(WAL_TREE)?;
+        if let Some(value) = wal.get(source)? {
+            let source = WriteAheadLog::tree_name(source);
+            self.copy_tree(&source, &WriteAheadLog::tree_name(target))?;
+            wal.insert(target, value)?;
+        }
+
+        // The collection is visible only after everything is copied.
+        self.collections.insert(target, value)?;
+        self.collections.flush()?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Renames a collection in the database.
+    /// * `name` - Current name of the collection.
+    /// * `new_name` - New name of the collection.
+    pub fn rename_collection(
+        &mut self,
+        name: &str,
+        new_name: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        self.copy_collection(name, new_name)?;
+        self.delete_collection(name)
+    }
+
+    /// Enables the write-ahead log for a stored collection.
+    /// Collections retrieved afterwards log their incremental
+    /// operations to the disk so that they survive a crash.
+    ///
+    /// Only one retrieved collection can change the stored collection
+    /// at a time. The first one to change it owns the log until it's
+    /// dropped and the changes of the other ones return an error.
+    /// * `name` - Name of the collection.
+    /// * `checkpoint_interval` - Operations between automatic saves.
+    ///   Use 0 to only save the collection manually.
+    pub fn enable_wal(
+        &mut self,
+        name: &str,
+        checkpoint_interval: usize,
+    ) -> Result<(), Box<dyn Error>> {
+        if !self.collections.contains_key(name)? {
+            return Err(err::COLLECTION_NOT_FOUND.into());
+        }
+
+        let interval = (checkpoint_interval as u64).to_be_bytes();
+        self.collections.open_tree(WAL_TREE)?.insert(name, &interval)?;
+        Ok(())
+    }
+
+    /// Disables the write-ahead log for a stored collection.
+    /// The logged operations are applied to the stored collection.
+    /// * `name` - Name of the collection.
+    pub fn disable_wal(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        if self.open_wal(name)?.is_none() {
+            return Ok(());
+        }
+
+        // Checkpoint the pending operations before removing the log.
+        let collection = self.get_collection(name)?;
+        self.save_collection(name, &collection)?;
+
+        self.collections.open_tree(WAL_TREE)?.remove(name)?;
+        self.collections.drop_tree(WriteAheadLog::tree_name(name))?;
+        Ok(())
+    }
+
+    /// Saves a collection to the disk for memory-mapped search.
+    /// The vectors and index are written to a flat file and the
+    /// metadata to a separate tree to be fetched only when needed.
+    /// * `name` - Name of the disk collection.
+    /// * `collection` - Vector collection to save.
+    pub fn save_disk_collection(
+        &mut self,
+        name: &str,
+        collection: &Collection,
+    ) -> Result<(), Box<dyn Error>> {
+        create_dir_all(self.path.join(DISK_TREE))?;
+        let data = self.collections.open_tree(Self::disk_tree_name(name))?;
+        DiskCollection::write(&self.disk_path(name), &data, collection)?;
+
+        // The config is kept for the options not in the index file.
+        let config = serde_json::to_vec(&collection.config)?;
+        self.collections.open_tree(DISK_TREE)?.insert(name, config)?;
+        self.collections.flush()?;
+        Ok(())
+    }
+
+    /// Opens a collection saved to the disk for memory-mapped search.
+    /// * `name` - Name of the disk collection.
+    pub fn get_disk_collection(
+        &self,
+        name: &str,
+    ) -> Result<DiskCollection, Box<dyn Error>> {
+        let config = match self.collections.open_tree(DISK_TREE)?.get(name)? {
+            Some(config) => config,
+            None => return Err(err::COLLECTION_NOT_FOUND.into()),
+        };
+
+        let data = self.collections.open_tree(Self::disk_tree_name(name))?;
+        let mut collection = DiskCollection::open(&self.disk_path(name), data)?;
+
+        // Disk collections saved before the config was stored.
+        if !config.is_empty() {
+            collection.config = serde_json::from_slice(&config)?;
+        }
+
+        Ok(collection)
+    }
+
+    /// Deletes a collection saved to the disk.
+    /// * `name` - Name of the disk collection.
+    pub fn delete_disk_collection(
+        &mut self,
+        name: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.collections.open_tree(DISK_TREE)?.remove(name)?.is_none() {
+            return Err(err::COLLECTION_NOT_FOUND.into());
+        }
+
+        let data = self.collections.open_tree(Self::disk_tree_name(name))?;
+        Collection::detach_data(&data)?;
+        self.collections.drop_tree(Self::disk_tree_name(name))?;
+        remove_file(self.disk_path(name))?;
+        Ok(())
+    }
+
+    /// Replaces the content of a tree with the content of another.
+    fn copy_tree(
+        &self,
+        source: &str,
+        target: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let source = self.collections.open_tree(source)?;
+        let target = self.collections.open_tree(target)?;
+        Collection::detach_data(&target)?;
+
+        let mut batch = sled::Batch::default();
+        for key in target.iter().keys() {
+            batch.remove(key?);
+        }
+
+        for entry in source.iter() {
+            let (key, value) = entry?;
+            batch.insert(key, value);
+        }
+
+        target.apply_batch(batch)?;
+        Ok(())
+    }
+
+    /// Returns the path of the index file of a disk collection.
+    fn disk_path(&self, name: &str) -> PathBuf {
+        self.path.join(DISK_TREE).join(format!("{}.index", name))
+    }
+
+    /// Returns the name of the tree storing a disk collection metadata.
+    fn disk_tree_name(name: &str) -> String {
+        format!("{}/{}", DISK_TREE, name)
+    }
+
+    /// Loads the full configuration of a collection if it's stored.
+    /// Collections stored before the config tree existed only have
+    /// the index options stored in the collection itself.
+    fn load_config(
+        &self,
+        name: &str,
+    ) -> Result<Option<Config>, Box<dyn Error>> {
+        match self.collections.open_tree(CONFIG_TREE)?.get(name)? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Opens the write-ahead log of a collection if it's enabled.
+    fn open_wal(
+        &self,
+        name: &str,
+    ) -> Result<Option<WriteAheadLog>, Box<dyn Error>> {
+        let value = self.collections.open_tree(WAL_TREE)?.get(name)?;
+        let interval = match value {
+            Some(value) => u64::from_be_bytes(value.as_ref().try_into()?),
+            None => return Ok(None),
+        };
+
+        let wal =
+            WriteAheadLog::open(&self.collections, name, interval as usize)?;
+        Ok(Some(wal))
+    }
+
     /// Returns the number of collections in the database.
     pub fn len(&self) -> usize {
         self.count
@@ -120,3 +488,116 @@ impl Database {
         self.count == 0
     }
 }
+
+/// Returns the name of the tree storing a collection metadata.
+pub(crate) fn data_tree_name(name: &str) -> String {
+    format!("{}/{}", DATA_TREE, name)
+}
+
+/// Returns the name of the tree storing a collection metadata indexes.
+pub(crate) fn index_tree_name(name: &str) -> String {
+    format!("{}/{}", INDEX_TREE, name)
+}
+
+/// Stores the collection and its metadata in a single transaction.
+/// The write-ahead log is cleared in the same transaction so that a
+/// crash never replays operations that are already stored.
+pub(crate) fn store_collection(
+    collections: &Db,
+    name: &str,
+    collection: &Collection,
+    wal: Option<&WriteAheadLog>,
+) -> Result<(), Box<dyn Error>> {
+    let value = collection.to_stored()?;
+    let info = bincode::serialize(&CollectionInfo::from(collection))?;
+
+    // The config is stored as JSON so that new options with default
+    // values can be added without breaking the stored collections.
+    let config = serde_json::to_vec(&collection.config)?;
+    let data = collections.open_tree(data_tree_name(name))?;
+    let data_batch = collection.data_batch(&data)?;
+    let index = collections.open_tree(index_tree_name(name))?;
+    let index_batch = collection.index_batch(&index)?;
+    let text = collection.text_index_bytes()?;
+    let sparse = collection.sparse_index_bytes()?;
+    let fields = collection.fields_bytes()?;
+    let ivf = collection.ivf_index_bytes()?;
+    let vectors = collection.vectors_bytes()?;
+
+    let tree: &Tree = collections;
+    let info_tree = collections.open_tree(INFO_TREE)?;
+    let config_tree = collections.open_tree(CONFIG_TREE)?;
+    let text_tree = collections.open_tree(TEXT_TREE)?;
+    let sparse_tree = collections.open_tree(SPARSE_TREE)?;
+    let fields_tree = collections.open_tree(FIELDS_TREE)?;
+    let ivf_tree = collections.open_tree(IVF_TREE)?;
+    let vectors_tree = collections.open_tree(VECTORS_TREE)?;
+    let mut trees = vec![
+        tree,
+        &info_tree,
+        &config_tree,
+        &data,
+        &index,
+        &text_tree,
+        &sparse_tree,
+        &fields_tree,
+        &ivf_tree,
+        &vectors_tree,
+    ];
+
+    // Keys of the logged operations to remove.
+    let mut keys = vec![];
+    if let Some(wal) = wal {
+        let log = wal.tree();
+        keys = log.iter().keys().collect::<Result<Vec<_>, _>>()?;
+        trees.push(log);
+    }
+
+    let result: TransactionResult<(), ()> =
+        trees.as_slice().transaction(|trees| {
+            trees[0].insert(name, value.as_slice())?;
+            trees[1].insert(name, info.as_slice())?;
+            trees[2].insert(name, config.as_slice())?;
+            trees[3].apply_batch(&data_batch)?;
+            trees[4].apply_batch(&index_batch)?;
+            match &text {
+                Some(text) => trees[5].insert(name, text.as_slice())?,
+                None => trees[5].remove(name)?,
+            };
+
+            match &sparse {
+                Some(sparse) => trees[6].insert(name, sparse.as_slice())?,
+                None => trees[6].remove(name)?,
+            };
+
+            match &fields {
+                Some(fields) => trees[7].insert(name, fields.as_slice())?,
+                None => trees[7].remove(name)?,
+            };
+
+            match &ivf {
+                Some(ivf) => trees[8].insert(name, ivf.as_slice())?,
+                None => trees[8].remove(name)?,
+            };
+
+            trees[9].insert(name, vectors.as_slice())?;
+            if let Some(log) = trees.get(10) {
+                for key in keys.iter() {
+                    log.remove(key)?;
+                }
+            }
+
+            Ok(())
+        });
+
+    result.map_err(|_| "Unable to store the collection.")?;
+    collections.flush()?;
+
+    // The changes are stored so they don't need to stay in memory.
+    collection.commit_data(&data);
+    for wal in wal.iter().chain(collection.wal().iter()) {
+        wal.reset();
+    }
+
+    Ok(())
+}
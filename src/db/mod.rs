@@ -1,9 +1,15 @@
 /// The vector database storing collections.
 pub mod database;
+/// The write-ahead log for incremental collection operations.
+pub mod wal;
 
 use crate::collection::*;
+use crate::disk::*;
 use crate::func::err;
-use sled::Db;
+use crate::wal::*;
+use serde::{Deserialize, Serialize};
+use sled::transaction::{TransactionResult, Transactional};
+use sled::{Db, Tree};
 use std::error::Error;
-use std::fs::remove_dir_all;
-use std::path::Path;
+use std::fs::{create_dir_all, remove_dir_all, remove_file};
+use std::path::{Path, PathBuf};
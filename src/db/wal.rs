@@ -0,0 +1,163 @@
+use super::database::*;
+use super::*;
+use parking_lot::{const_mutex, Mutex};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Tree storing the checkpoint interval of collections with WAL.
+pub const WAL_TREE: &str = "wal";
+
+/// The logs owned by the loaded collections.
+static OWNED: Mutex<Vec<Tree>> = const_mutex(Vec::new());
+
+/// The write-ahead log attached to a persisted collection.
+///
+/// Operations are appended to a dedicated sled tree after they are
+/// applied in memory. The log is cleared whenever the collection is
+/// checkpointed into its main stored form.
+///
+/// Only one collection handle can own the log to append to it at a
+/// time so that the operations of different handles are never mixed
+/// in the log. A handle owns the log from its first logged operation
+/// until the log is dropped with the handle.
+pub struct WriteAheadLog {
+    name: String,
+    collections: Db,
+    log: Tree,
+    // Operations to log before checkpointing. 0 to disable.
+    checkpoint_interval: usize,
+    pending: AtomicUsize,
+    owned: AtomicBool,
+}
+
+impl WriteAheadLog {
+    /// Opens the log of a collection in the database.
+    /// * `collections`: Database storing the collections.
+    /// * `name`: Name of the collection.
+    /// * `checkpoint_interval`: Operations between checkpoints.
+    pub fn open(
+        collections: &Db,
+        name: &str,
+        checkpoint_interval: usize,
+    ) -> Result<Self, Box<dyn Error>> {
+        let log = collections.open_tree(Self::tree_name(name))?;
+        let pending = AtomicUsize::new(log.len());
+
+        Ok(Self {
+            name: name.to_string(),
+            collections: collections.clone(),
+            log,
+            checkpoint_interval,
+            pending,
+            owned: AtomicBool::new(false),
+        })
+    }
+
+    /// Takes the ownership of the log to append the operations of
+    /// a collection handle. Fails if another handle owns the log.
+    pub fn own(&self) -> Result<(), Box<dyn Error>> {
+        if self.owned.load(Ordering::Acquire) {
+            return Ok(());
+        }
+
+        let mut owned = OWNED.lock();
+        if owned.iter().any(|log| same_log(log, &self.log)) {
+            return Err(err::WAL_IN_USE.into());
+        }
+
+        owned.push(self.log.clone());
+        self.owned.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// Returns true if this handle owns the same log as the other.
+    pub fn owns(&self, other: &WriteAheadLog) -> bool {
+        self.owned.load(Ordering::Acquire) && same_log(&self.log, &other.log)
+    }
+
+    /// Returns true if a collection handle owns the log.
+    pub fn is_owned(&self) -> bool {
+        OWNED.lock().iter().any(|log| same_log(log, &self.log))
+    }
+
+    /// Returns the name of the sled tree storing the log.
+    pub fn tree_name(name: &str) -> String {
+        format!("{}/{}", WAL_TREE, name)
+    }
+
+    /// Appends an operation to the log and flushes it to the disk.
+    pub fn append(&self, operation: &Operation) -> Result<(), Box<dyn Error>> {
+        self.own()?;
+
+        // Monotonic IDs keep the log in the order of operations.
+        let key = self.collections.generate_id()?.to_be_bytes();
+        let value = bincode::serialize(operation)?;
+        self.log.insert(key, value)?;
+        self.log.flush()?;
+
+        self.pending.fetch_add(1, Ordering::AcqRel);
+        Ok(())
+    }
+
+    /// Applies the logged operations to the collection in order.
+    /// * `collection`: Collection loaded from its stored form.
+    pub fn replay(
+        &self,
+        collection: &mut Collection,
+    ) -> Result<(), Box<dyn Error>> {
+        for entry in self.log.iter() {
+            let (_, value) = entry?;
+            match bincode::deserialize(&value)? {
+                Operation::Insert(record) => collection.insert(&record)?,
+                Operation::Update(id, record) => {
+                    collection.update(&id, &record)?
+                }
+                Operation::Delete(id) => collection.delete(&id)?,
+                Operation::UpdateData(id, data) => {
+                    collection.update_data(&id, &data)?
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the log reached the checkpoint interval.
+    pub fn should_checkpoint(&self) -> bool {
+        let pending = self.pending.load(Ordering::Acquire);
+        self.checkpoint_interval > 0 && pending >= self.checkpoint_interval
+    }
+
+    /// Resets the number of operations since the last checkpoint.
+    pub fn reset(&self) {
+        self.pending.store(0, Ordering::Release);
+    }
+
+    /// Saves the collection and clears the log atomically.
+    /// * `collection`: Collection containing all logged operations.
+    pub fn checkpoint(
+        &self,
+        collection: &Collection,
+    ) -> Result<(), Box<dyn Error>> {
+        store_collection(&self.collections, &self.name, collection, Some(self))
+    }
+
+    /// Returns the tree storing the logged operations.
+    pub fn tree(&self) -> &Tree {
+        &self.log
+    }
+}
+
+impl Drop for WriteAheadLog {
+    fn drop(&mut self) {
+        if *self.owned.get_mut() {
+            OWNED.lock().retain(|log| !same_log(log, &self.log));
+        }
+    }
+}
+
+/// Returns true if both are the same log tree. The database returns
+/// the same tree instance for the same name.
+fn same_log(a: &Tree, b: &Tree) -> bool {
+    std::ptr::eq(a.deref(), b.deref())
+}
@@ -0,0 +1,188 @@
+use super::*;
+
+/// Number of slots tracked by a word of the tombstone bitmap.
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// The vectors of a collection stored back to back by their slot.
+///
+/// The vector of a slot starts at `slot * dimension` so it's found
+/// without hashing and the vectors read by a search are close to each
+/// other in memory. Deleted vectors leave a tombstone in the bitmap
+/// and their values are kept until the slot is reused.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub(crate) struct VectorArena {
+    dimension: usize,
+    values: Vec<f32>,
+    /// Bits of the slots without a vector.
+    tombstones: Vec<u64>,
+    slots: usize,
+    count: usize,
+}
+
+impl VectorArena {
+    /// Creates the arena from the vectors by their ID.
+    pub fn from_map(vectors: &HashMap<VectorID, Vector>) -> Self {
+        let mut arena = Self::default();
+        if let Some(vector) = vectors.values().next() {
+            arena.dimension = vector.len();
+        }
+
+        let slots = vectors.keys().map(|id| id.0 as usize + 1).max();
+        arena.reserve(slots.unwrap_or(0));
+        for (id, vector) in vectors.iter() {
+            arena.insert(*id, &vector.0);
+        }
+
+        arena
+    }
+
+    /// Returns the number of vectors in the arena.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns true if the arena has no vectors.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns true if the slot of the ID has a vector.
+    pub fn contains(&self, id: &VectorID) -> bool {
+        let slot = id.0 as usize;
+        slot < self.slots && !self.is_tombstone(slot)
+    }
+
+    /// Returns the vector values of the ID if any.
+    pub fn get(&self, id: &VectorID) -> Option<&[f32]> {
+        match self.contains(id) {
+            true => Some(self.slice(id.0 as usize)),
+            false => None,
+        }
+    }
+
+    /// Returns the first ID with a vector.
+    pub fn first(&self) -> Option<VectorID> {
+        for (i, word) in self.tombstones.iter().enumerate() {
+            let slot = i * WORD_BITS + word.trailing_ones() as usize;
+            if *word != u64::MAX && slot < self.slots {
+                return Some(slot.into());
+            }
+        }
+
+        None
+    }
+
+    /// Stores the vector in the slot of the ID. The arena grows to the
+    /// slot if needed and the slots in between are left empty.
+    pub fn insert(&mut self, id: VectorID, vector: &[f32]) {
+        // The dimension is set by the first vector.
+        if self.count == 0 && self.dimension != vector.len() {
+            self.dimension = vector.len();
+            self.values = vec![0.0; self.slots * self.dimension];
+        }
+
+        let slot = id.0 as usize;
+        if slot >= self.slots {
+            self.reserve(slot + 1);
+        }
+
+        if self.is_tombstone(slot) {
+            self.tombstones[slot / WORD_BITS] &= !(1 << (slot % WORD_BITS));
+            self.count += 1;
+        }
+
+        let start = slot * self.dimension;
+        let values = &mut self.values[start..start + self.dimension];
+        values.copy_from_slice(vector);
+    }
+
+    /// Removes the vector of the ID leaving a tombstone in its slot.
+    pub fn remove(&mut self, id: &VectorID) {
+        if self.contains(id) {
+            let slot = id.0 as usize;
+            self.tombstones[slot / WORD_BITS] |= 1 << (slot % WORD_BITS);
+            self.count -= 1;
+        }
+    }
+
+    /// Returns the IDs and values of the vectors in the slot order.
+    pub fn iter(&self) -> impl Iterator<Item = (VectorID, &[f32])> {
+        let slots = (0..self.slots).filter(|slot| !self.is_tombstone(*slot));
+        slots.map(|slot| (slot.into(), self.slice(slot)))
+    }
+
+    /// Returns the IDs and values of the vectors in parallel.
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = (VectorID, &[f32])> {
+        let slots = (0..self.slots).into_par_iter();
+        let slots = slots.filter(|slot| !self.is_tombstone(*slot));
+        slots.map(|slot| (slot.into(), self.slice(slot)))
+    }
+
+    /// Grows the arena to the number of slots with empty slots.
+    fn reserve(&mut self, slots: usize) {
+        if slots <= self.slots {
+            return;
+        }
+
+        self.values.resize(slots * self.dimension, 0.0);
+        self.tombstones.resize(slots.div_ceil(WORD_BITS), 0);
+        for slot in self.slots..slots {
+            self.tombstones[slot / WORD_BITS] |= 1 << (slot % WORD_BITS);
+        }
+
+        self.slots = slots;
+    }
+
+    fn is_tombstone(&self, slot: usize) -> bool {
+        self.tombstones[slot / WORD_BITS] & (1 << (slot % WORD_BITS)) != 0
+    }
+
+    fn slice(&self, slot: usize) -> &[f32] {
+        let start = slot * self.dimension;
+        &self.values[start..start + self.dimension]
+    }
+}
+
+impl Index<&VectorID> for VectorArena {
+    type Output = [f32];
+    fn index(&self, index: &VectorID) -> &Self::Output {
+        match self.get(index) {
+            Some(vector) => vector,
+            None => panic!("The vector {} is not found.", index.0),
+        }
+    }
+}
+
+impl VectorStore for VectorArena {
+    fn get_vector(&self, vector_id: &VectorID) -> Option<&[f32]> {
+        self.get(vector_id)
+    }
+}
+
+/// Serializes the arena as a map of the vectors by their ID for serde
+/// `with`. This is the format of the vectors in the collections stored
+/// before the arena and it's kept for the indexes of the fields.
+pub(crate) mod vector_map {
+    use super::*;
+    use serde::ser::SerializeMap;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        vectors: &VectorArena,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(vectors.len()))?;
+        for (id, vector) in vectors.iter() {
+            map.serialize_entry(&id, vector)?;
+        }
+
+        map.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<VectorArena, D::Error> {
+        let vectors = HashMap::<VectorID, Vector>::deserialize(deserializer)?;
+        Ok(VectorArena::from_map(&vectors))
+    }
+}
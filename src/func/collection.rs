@@ -1,7 +1,37 @@
 use super::*;
+use rand::seq::SliceRandom;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::time::{Duration, Instant};
+
+/// The index type used by the collection for the vector search.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IndexType {
+    /// Approximate search with the HNSW graph.
+    #[default]
+    Hnsw,
+    /// Exact search scanning all vectors without a graph. This is
+    /// recommended for collections with less than 10,000 vectors.
+    Flat,
+    /// Approximate search scanning the inverted file lists of the
+    /// centroids nearest to the query. The centroids are trained
+    /// with k-means when the collection is built.
+    Ivf {
+        /// Number of centroids and lists.
+        lists: usize,
+        /// Default number of lists to scan in the search.
+        probes: usize,
+    },
+}
 
 /// The collection HNSW index configuration.
-#[derive(Serialize, Deserialize, Clone, Copy)]
+///
+/// The options after `ml` are stored separately from the collection
+/// and must have a default value to load the collections stored
+/// before they existed.
+///
+/// Unlike v0.2, the configuration is not `Copy` because the schema
+/// and the field names are owned. Use `clone` to copy it instead.
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Config {
     /// Nodes to consider during construction.
     pub ef_construction: usize,
@@ -9,6 +39,25 @@ pub struct Config {
     pub ef_search: usize,
     /// Layer multiplier. The optimal value is `1/ln(M)`.
     pub ml: f32,
+    /// Schema to validate the metadata of the records against.
+    #[serde(default)]
+    pub schema: Option<Schema>,
+    /// Scales the vectors to the unit length on insertion and search
+    /// so that the Euclidean distance ranks like the cosine distance.
+    #[serde(default)]
+    pub normalize: bool,
+    /// Metadata path of the text field to index for the text search
+    /// like `content`. Records without a text value are not indexed.
+    #[serde(default)]
+    pub text_field: Option<String>,
+    /// Names of the vector fields of the records in addition to the
+    /// main vector. Each field has its own index and dimension.
+    #[serde(default)]
+    pub vector_fields: Vec<String>,
+    /// Index type of the vector search. The HNSW options above are
+    /// ignored by the flat and IVF indexes.
+    #[serde(default)]
+    pub index: IndexType,
 }
 
 impl Default for Config {
@@ -16,17 +65,127 @@ impl Default for Config {
     /// * `ef_construction`: 40
     /// * `ef_search`: 15
     /// * `ml`: 0.3
+    /// * `schema`: None
+    /// * `normalize`: false
+    /// * `text_field`: None
+    /// * `vector_fields`: []
+    /// * `index`: Hnsw
     fn default() -> Self {
-        Self { ef_construction: 40, ef_search: 15, ml: 0.3 }
+        Self {
+            ef_construction: 40,
+            ef_search: 15,
+            ml: 0.3,
+            schema: None,
+            normalize: false,
+            text_field: None,
+            vector_fields: vec![],
+            index: IndexType::Hnsw,
+        }
+    }
+}
+
+impl Config {
+    /// Validates the metadata against the schema if any.
+    pub(crate) fn validate(
+        &self,
+        data: &Metadata,
+    ) -> Result<(), Box<dyn Error>> {
+        match &self.schema {
+            Some(schema) => Ok(schema.validate(data)?),
+            None => Ok(()),
+        }
+    }
+
+    /// Validates the vector and normalizes it if configured.
+    pub(crate) fn prepare_vector(
+        &self,
+        vector: &Vector,
+    ) -> Result<Vector, Box<dyn Error>> {
+        vector.validate()?;
+        Ok(self.normalize_vector(vector))
+    }
+
+    /// Trains the IVF index of the vectors if it's configured.
+    pub(crate) fn ivf_index(&self, vectors: &VectorArena) -> Option<IvfIndex> {
+        match self.index {
+            IndexType::Ivf { lists, .. } => {
+                Some(IvfIndex::build(lists, vectors))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the text field in the metadata if any.
+    pub(crate) fn text<'a>(&self, data: &'a Metadata) -> Option<&'a str> {
+        let path = self.text_field.as_ref()?;
+        data.get(path)?.as_str()
+    }
+
+    /// Normalizes the vector if configured.
+    pub(crate) fn normalize_vector(&self, vector: &Vector) -> Vector {
+        match self.normalize {
+            true => vector.normalize(),
+            false => vector.clone(),
+        }
+    }
+}
+
+/// The index options of the configuration stored in the collection.
+/// The other options are stored separately as the collection format
+/// predates them.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct BaseConfig {
+    ef_construction: usize,
+    ef_search: usize,
+    ml: f32,
+}
+
+impl From<&Config> for BaseConfig {
+    fn from(config: &Config) -> Self {
+        Self {
+            ef_construction: config.ef_construction,
+            ef_search: config.ef_search,
+            ml: config.ml,
+        }
+    }
+}
+
+impl From<BaseConfig> for Config {
+    fn from(base: BaseConfig) -> Self {
+        Self {
+            ef_construction: base.ef_construction,
+            ef_search: base.ef_search,
+            ml: base.ml,
+            ..Default::default()
+        }
+    }
+}
+
+/// Serializes the config as the base config for serde `with`.
+pub(crate) mod base_config {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        config: &Config,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        BaseConfig::from(config).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Config, D::Error> {
+        Ok(BaseConfig::deserialize(deserializer)?.into())
     }
 }
 
-struct IndexConstruction<'a> {
-    search_pool: SearchPool,
-    top_layer: LayerID,
-    base_layer: &'a [RwLock<BaseNode>],
-    vectors: &'a HashMap<VectorID, Vector>,
-    config: &'a Config,
+pub(super) struct IndexConstruction<'a> {
+    pub search_pool: SearchPool,
+    pub top_layer: LayerID,
+    pub base_layer: &'a [RwLock<BaseNode>],
+    pub vectors: &'a VectorArena,
+    pub config: &'a Config,
 }
 
 impl<'a> IndexConstruction<'a> {
@@ -34,7 +193,7 @@ impl<'a> IndexConstruction<'a> {
     /// * `vector_id`: Vector ID to insert.
     /// * `layer`: Layer to insert into.
     /// * `layers`: Upper layers.
-    fn insert(
+    pub fn insert(
         &self,
         vector_id: &VectorID,
         layer: &LayerID,
@@ -46,14 +205,10 @@ impl<'a> IndexConstruction<'a> {
         insertion.ef = self.config.ef_construction;
 
         // Find the first valid vector ID to push.
-        let validator = |i: usize| self.vectors.get(&i.into()).is_some();
-        let valid_id = (0..self.vectors.len())
-            .into_par_iter()
-            .find_first(|i| validator(*i))
-            .unwrap();
+        let valid_id = self.vectors.first().unwrap();
 
         search.reset();
-        search.push(&valid_id.into(), vector, self.vectors);
+        search.push(&valid_id, vector, self.vectors);
 
         for current_layer in self.top_layer.descend() {
             if current_layer <= *layer {
@@ -83,13 +238,13 @@ impl<'a> IndexConstruction<'a> {
             let distance = candidate.distance;
 
             // Function to sort the vectors by distance.
-            let ordering = |id: &VectorID| {
-                if !id.is_valid() {
-                    Ordering::Greater
-                } else {
-                    let other = &self.vectors[id];
-                    distance.cmp(&old.distance(other).into())
+            // Deleted vectors are treated the same as empty slots.
+            let ordering = |id: &VectorID| match self.vectors.get(id) {
+                Some(other) if id.is_valid() => {
+                    let other = squared_euclidean(old, other);
+                    OrderedFloat(other).cmp(&distance)
                 }
+                _ => Ordering::Greater,
             };
 
             // Find the correct index to insert at to keep the order.
@@ -99,7 +254,7 @@ impl<'a> IndexConstruction<'a> {
                 .unwrap_or_else(|error| error);
 
             self.base_layer[&vid].write().insert(index, vector_id);
-            self.base_layer[vector_id].write().set(i, vector_id);
+            self.base_layer[vector_id].write().set(i, &vid);
         }
 
         self.search_pool.push(&(search, insertion));
@@ -110,20 +265,44 @@ impl<'a> IndexConstruction<'a> {
 #[derive(Serialize, Deserialize)]
 pub struct Collection {
     /// The collection configuration object.
+    #[serde(with = "base_config")]
     pub config: Config,
     // Private fields below.
-    data: HashMap<VectorID, Metadata>,
-    vectors: HashMap<VectorID, Vector>,
-    slots: Vec<VectorID>,
-    base_layer: Vec<BaseNode>,
-    upper_layers: Vec<Vec<UpperNode>>,
+    pub(super) data: DataStore,
+    // Vectors stored in a separate tree in the arena format.
+    #[serde(with = "vector_map")]
+    pub(super) vectors: VectorArena,
+    pub(super) slots: Vec<VectorID>,
+    pub(super) base_layer: Vec<BaseNode>,
+    pub(super) upper_layers: Vec<Vec<UpperNode>>,
     // Utility fields.
-    count: usize,
-    dimension: usize,
+    pub(super) count: usize,
+    pub(super) dimension: usize,
+    // Write-ahead log when the collection is persisted with WAL.
+    #[serde(skip)]
+    pub(super) wal: Option<WriteAheadLog>,
+    // Metadata indexes stored in a separate tree.
+    #[serde(skip)]
+    pub(super) indexes: HashMap<String, MetadataIndex>,
+    // Text index of the configured field stored in a separate tree.
+    #[serde(skip)]
+    pub(super) text_index: Option<TextIndex>,
+    // Sparse vectors of the records stored in a separate tree.
+    #[serde(skip)]
+    pub(super) sparse: SparseIndex,
+    // Named vector fields stored in a separate tree.
+    #[serde(skip)]
+    pub(super) fields: VectorFields,
+    // Inverted file lists when the collection uses the IVF index.
+    #[serde(skip)]
+    pub(super) ivf: Option<IvfIndex>,
+    // Search counters aggregated since the collection is loaded.
+    #[serde(skip)]
+    pub(super) counters: SearchMetrics,
 }
 
 impl Index<&VectorID> for Collection {
-    type Output = Vector;
+    type Output = [f32];
     fn index(&self, index: &VectorID) -> &Self::Output {
         &self.vectors[index]
     }
@@ -132,15 +311,24 @@ impl Index<&VectorID> for Collection {
 impl Collection {
     /// Creates an empty collection with the given configuration.
     pub fn new(config: &Config) -> Self {
+        let text_index =
+            config.text_field.as_ref().map(|_| TextIndex::default());
         Self {
-            config: *config,
+            config: config.clone(),
             count: 0,
             dimension: 0,
-            data: HashMap::new(),
-            vectors: HashMap::new(),
+            data: DataStore::default(),
+            vectors: VectorArena::default(),
             slots: vec![],
             base_layer: vec![],
             upper_layers: vec![],
+            wal: None,
+            indexes: HashMap::new(),
+            text_index,
+            sparse: SparseIndex::default(),
+            fields: VectorFields::new(config),
+            ivf: config.ivf_index(&VectorArena::default()),
+            counters: SearchMetrics::default(),
         }
     }
 
@@ -177,9 +365,113 @@ impl Collection {
             return Err(message.into());
         }
 
+        // Ensure that the vectors are finite and
+        // the metadata matches the schema.
+        for record in records.iter() {
+            record.vector.validate()?;
+            record.validate_sparse()?;
+            config.validate(&record.data)?;
+        }
+
+        let fields = VectorFields::build(config, records)?;
+
+        let mut vectors = VectorArena::default();
+        for (i, record) in records.iter().enumerate() {
+            let vector = config.normalize_vector(&record.vector);
+            vectors.insert(i.into(), &vector.0);
+        }
+
+        // Only the HNSW index needs the graph layers.
+        let (base_layer, upper_layers) = match config.index {
+            IndexType::Hnsw => Self::build_layers(config, &vectors),
+            _ => (vec![], vec![]),
+        };
+
+        let ivf = config.ivf_index(&vectors);
+
+        let data = records
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (i.into(), item.data.clone()))
+            .collect::<HashMap<VectorID, Metadata>>()
+            .into();
+
+        let text_index = config.text_field.as_ref().map(|_| {
+            let mut index = TextIndex::default();
+            for (i, record) in records.iter().enumerate() {
+                if let Some(text) = config.text(&record.data) {
+                    index.insert(i.into(), text);
+                }
+            }
+
+            index
+        });
+
+        let mut sparse = SparseIndex::default();
+        for (i, record) in records.iter().enumerate() {
+            if let Some(vector) = &record.sparse {
+                sparse.insert(i.into(), vector);
+            }
+        }
+
+        // Add IDs to the slots.
+        let slots = (0..vectors.len()).map(|i| i.into()).collect();
+
+        Ok(Self {
+            data,
+            vectors,
+            base_layer,
+            upper_layers,
+            slots,
+            dimension,
+            config: config.clone(),
+            count: records.len(),
+            wal: None,
+            indexes: HashMap::new(),
+            text_index,
+            sparse,
+            fields,
+            ivf,
+            counters: SearchMetrics::default(),
+        })
+    }
+
+    /// Builds the HNSW graph layers of the vectors.
+    fn build_layers(
+        config: &Config,
+        vectors: &VectorArena,
+    ) -> (Vec<BaseNode>, Vec<Vec<UpperNode>>) {
+        Self::construct_layers(config, vectors, vectors.len(), true)
+    }
+
+    /// Builds the upper HNSW layers of the vectors in the slots
+    /// without linking the base layer. The upper layers hold the
+    /// lowest vector IDs so they stay valid for the existing links.
+    /// * `config`: Collection configuration.
+    /// * `vectors`: Vectors of the collection.
+    /// * `slots`: Number of slots including the deleted vectors.
+    pub(super) fn build_upper_layers(
+        config: &Config,
+        vectors: &VectorArena,
+        slots: usize,
+    ) -> Vec<Vec<UpperNode>> {
+        match vectors.is_empty() {
+            true => vec![],
+            false => Self::construct_layers(config, vectors, slots, false).1,
+        }
+    }
+
+    /// Constructs the graph layers of the vectors in the slots.
+    /// The base layer is only linked if `base` is true.
+    fn construct_layers(
+        config: &Config,
+        vectors: &VectorArena,
+        slots: usize,
+        base: bool,
+    ) -> (Vec<BaseNode>, Vec<Vec<UpperNode>>) {
         // Find the number of layers.
 
-        let mut len = records.len();
+        let mut len = slots;
         let mut layers = Vec::new();
 
         loop {
@@ -206,12 +498,6 @@ impl Collection {
         // construction progresses, while preserving randomness in
         // each point's layer and insertion order.
 
-        let vectors = records
-            .par_iter()
-            .enumerate()
-            .map(|(i, item)| (i.into(), item.vector.clone()))
-            .collect::<HashMap<VectorID, Vector>>();
-
         // Figure out how many nodes will go on each layer.
         // This helps us allocate memory capacity for each
         // layer in advance, and also helps enable batch
@@ -227,10 +513,10 @@ impl Collection {
 
         // Create index constructor.
 
-        let search_pool = SearchPool::new(vectors.len());
+        let search_pool = SearchPool::new(slots);
         let mut upper_layers = vec![vec![]; top_layer.0];
-        let base_layer = vectors
-            .par_iter()
+        let base_layer = (0..slots)
+            .into_par_iter()
             .map(|_| RwLock::new(BaseNode::default()))
             .collect::<Vec<_>>();
 
@@ -238,17 +524,26 @@ impl Collection {
             base_layer: &base_layer,
             search_pool,
             top_layer,
-            vectors: &vectors,
+            vectors,
             config,
         };
 
         // Initialize data for layers.
 
+        // The first vector is the entry point of the search and the
+        // deleted vectors are skipped.
+        let first = vectors.first();
         for (layer, range) in ranges {
-            let end = range.end;
+            if layer.is_zero() && !base {
+                break;
+            }
 
+            let end = range.end;
             range.into_par_iter().for_each(|i: usize| {
-                state.insert(&i.into(), &layer, &upper_layers)
+                let id = i.into();
+                if vectors.contains(&id) && Some(id) != first {
+                    state.insert(&id, &layer, &upper_layers)
+                }
             });
 
             // Copy the base layer state to the upper layer.
@@ -260,29 +555,10 @@ impl Collection {
             }
         }
 
-        let data = records
-            .iter()
-            .enumerate()
-            .map(|(i, item)| (i.into(), item.data.clone()))
-            .collect();
-
         // Unwrap the base nodes for the base layer.
         let base_iter = base_layer.into_par_iter();
         let base_layer = base_iter.map(|node| node.into_inner()).collect();
-
-        // Add IDs to the slots.
-        let slots = (0..vectors.len()).map(|i| i.into()).collect();
-
-        Ok(Self {
-            data,
-            vectors,
-            base_layer,
-            upper_layers,
-            slots,
-            dimension,
-            config: *config,
-            count: records.len(),
-        })
+        (base_layer, upper_layers)
     }
 
     /// Inserts a vector record into the collection.
@@ -294,10 +570,9 @@ impl Collection {
         }
 
         // Ensure the vector dimension matches the collection config.
-        // If it's the first record, set the dimension.
-        if self.vectors.is_empty() && self.dimension == 0 {
-            self.dimension = record.vector.len();
-        } else if record.vector.len() != self.dimension {
+        // The first record sets the dimension once it's validated.
+        let first = self.vectors.is_empty() && self.dimension == 0;
+        if !first && record.vector.len() != self.dimension {
             let message = format!(
                 "Invalid vector dimension. Expected dimension of {}.",
                 self.dimension
@@ -306,12 +581,30 @@ impl Collection {
             return Err(message.into());
         }
 
+        let vector = self.config.prepare_vector(&record.vector)?;
+        record.validate_sparse()?;
+        self.fields.validate(&record.vectors)?;
+        self.config.validate(&record.data)?;
+        if first {
+            self.dimension = record.vector.len();
+        }
+
+        // Only the handle owning the write-ahead log can change the
+        // collection so that its operations are logged.
+        self.own_wal()?;
+
         // Create a new vector ID using the next available slot.
         let id: VectorID = self.slots.len().into();
 
         // Insert the new vector and data.
-        self.vectors.insert(id, record.vector.clone());
+        self.vectors.insert(id, &vector.0);
         self.data.insert(id, record.data.clone());
+        self.index_data(&id, &record.data);
+        if let Some(sparse) = &record.sparse {
+            self.sparse.insert(id, sparse);
+        }
+
+        self.fields.insert(id, &record.vectors)?;
 
         // Add new vector id to the slots.
         self.slots.push(id);
@@ -321,9 +614,10 @@ impl Collection {
 
         // This operation is last because it depends on
         // the updated vectors data.
-        self.insert_to_layers(&id);
+        self.insert_to_index(&id);
 
-        Ok(())
+        self.write_ahead(Operation::Insert(record.clone()))?;
+        self.try_checkpoint()
     }
 
     /// Deletes a vector record from the collection.
@@ -334,7 +628,11 @@ impl Collection {
             return Err(err::RECORD_NOT_FOUND.into());
         }
 
-        self.delete_from_layers(id);
+        self.own_wal()?;
+        self.unindex_data(id)?;
+        self.sparse.remove(id);
+        self.fields.remove(id)?;
+        self.delete_from_index(id);
 
         // Update the collection data.
         self.vectors.remove(id);
@@ -344,7 +642,8 @@ impl Collection {
         // Update the collection count.
         self.count -= 1;
 
-        Ok(())
+        self.write_ahead(Operation::Delete(*id))?;
+        self.try_checkpoint()
     }
 
     /// Updates a vector record in the collection.
@@ -359,15 +658,68 @@ impl Collection {
             return Err(err::RECORD_NOT_FOUND.into());
         }
 
-        // Remove the old vector from the index layers.
-        self.delete_from_layers(id);
+        let vector = self.config.prepare_vector(&record.vector)?;
+        record.validate_sparse()?;
+        self.fields.validate(&record.vectors)?;
+        self.config.validate(&record.data)?;
+        self.own_wal()?;
+        self.replace_data(id, &record.data)?;
 
-        // Insert the updated vector and data.
-        self.vectors.insert(*id, record.vector.clone());
-        self.data.insert(*id, record.data.clone());
-        self.insert_to_layers(id);
+        match &record.sparse {
+            Some(sparse) => self.sparse.insert(*id, sparse),
+            None => self.sparse.remove(id),
+        }
 
-        Ok(())
+        self.fields.remove(id)?;
+        self.fields.insert(*id, &record.vectors)?;
+
+        // Only re-index the vector if it changed since the graph
+        // is expensive to update and changes with every insertion.
+        if !vector.is_identical(&self.vectors[id].into()) {
+            self.delete_from_index(id);
+            self.vectors.insert(*id, &vector.0);
+            self.insert_to_index(id);
+        }
+
+        self.write_ahead(Operation::Update(*id, record.clone()))?;
+        self.try_checkpoint()
+    }
+
+    /// Replaces the metadata of a record without re-indexing its vector.
+    /// * `id`: Vector ID to update.
+    /// * `data`: New metadata of the record.
+    pub fn update_data(
+        &mut self,
+        id: &VectorID,
+        data: &Metadata,
+    ) -> Result<(), Box<dyn Error>> {
+        if !self.contains(id) {
+            return Err(err::RECORD_NOT_FOUND.into());
+        }
+
+        self.config.validate(data)?;
+        self.own_wal()?;
+        self.replace_data(id, data)?;
+        self.write_ahead(Operation::UpdateData(*id, data.clone()))?;
+        self.try_checkpoint()
+    }
+
+    /// Applies a JSON merge patch to the metadata of a record without
+    /// re-indexing its vector. See `Metadata::merge` for the rules.
+    /// * `id`: Vector ID to update.
+    /// * `patch`: Changes to merge into the current metadata.
+    pub fn patch_data(
+        &mut self,
+        id: &VectorID,
+        patch: &Metadata,
+    ) -> Result<(), Box<dyn Error>> {
+        if !self.contains(id) {
+            return Err(err::RECORD_NOT_FOUND.into());
+        }
+
+        let mut data = self.data.get(id)?;
+        data.merge(patch);
+        self.update_data(id, &data)
     }
 
     /// Returns the vector record associated with the ID.
@@ -377,9 +729,11 @@ impl Collection {
             return Err(err::RECORD_NOT_FOUND.into());
         }
 
-        let vector = self.vectors[id].clone();
-        let data = self.data[id].clone();
-        Ok(Record::new(&vector, &data))
+        let vector = self.vectors[id].into();
+        let data = self.data.get(id)?;
+        let sparse = self.sparse.get(id).cloned();
+        let vectors = self.fields.get(id);
+        Ok(Record { vector, data, sparse, vectors })
     }
 
     /// Searches the collection for the nearest neighbors.
@@ -390,112 +744,1196 @@ impl Collection {
         vector: &Vector,
         n: usize,
     ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
-        let mut search = Search::default();
-
-        if self.vectors.is_empty() {
-            return Ok(vec![]);
-        }
+        let nearest = self.search_ids(vector, n)?;
+        self.to_results(nearest)
+    }
 
-        // Find the first valid vector ID from the slots.
-        let slots_iter = self.slots.as_slice().into_par_iter();
-        let vector_id = match slots_iter.find_first(|id| id.is_valid()) {
-            Some(id) => id,
-            None => return Err("Unable to initiate search.".into()),
+    /// Searches the IVF index scanning the lists of the `probes`
+    /// nearest centroids instead of the configured default. More
+    /// probes improve the recall but make the search slower.
+    /// * `vector`: Vector to search.
+    /// * `n`: Number of neighbors to return.
+    /// * `probes`: Number of lists to scan.
+    pub fn search_with_probes(
+        &self,
+        vector: &Vector,
+        n: usize,
+        probes: usize,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+        let ivf = match &self.ivf {
+            Some(ivf) => ivf,
+            None => return Err(err::INDEX_TYPE_UNSUPPORTED.into()),
         };
 
-        search.visited.resize_capacity(self.vectors.len());
-        search.push(vector_id, vector, &self.vectors);
+        let vector = &self.config.prepare_vector(vector)?;
+        let nearest = ivf.search(vector, &self.vectors, n, probes);
+        self.to_results(nearest)
+    }
 
-        for layer in LayerID(self.upper_layers.len()).descend() {
-            search.ef = if layer.is_zero() { self.config.ef_search } else { 5 };
+    /// Searches the collection for the nearest neighbors skipping the
+    /// first `offset` neighbors. A page is the same as the slice of a
+    /// search for `offset + n` neighbors and the neighbors with the
+    /// same distance are ordered by their ID.
+    ///
+    /// The pages of the same query are consistent while the collection
+    /// doesn't change and `offset + n` is at most `ef_search`. Beyond
+    /// that, each page widens the HNSW search to `offset + n` so the
+    /// approximate neighbors of the pages might overlap or skip some
+    /// records. Increase `ef_search` to page through more neighbors.
+    /// * `vector`: Vector to search.
+    /// * `offset`: Number of nearest neighbors to skip.
+    /// * `n`: Number of neighbors to return.
+    pub fn search_with_offset(
+        &self,
+        vector: &Vector,
+        offset: usize,
+        n: usize,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+        let vector = &self.config.prepare_vector(vector)?;
+        let ef = max(self.config.ef_search, offset + n);
+        let mut nearest = self.search_candidates(vector, ef)?;
+        nearest.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
 
-            if layer.0 == 0 {
-                let layer = self.base_layer.as_slice();
-                search.search(layer, vector, &self.vectors, M * 2);
-            } else {
-                let layer = self.upper_layers[layer.0 - 1].as_slice();
-                search.search(layer, vector, &self.vectors, M);
-            }
+        let page = nearest.into_iter().skip(offset).take(n).collect();
+        self.to_results(page)
+    }
 
-            if !layer.is_zero() {
-                search.cull();
-            }
-        }
+    /// Searches the collection like `search` and also returns the
+    /// statistics of the search to inspect what the index did, for
+    /// example, when the recall is lower than expected.
+    /// * `vector`: Vector to search.
+    /// * `n`: Number of neighbors to return.
+    pub fn search_explain(
+        &self,
+        vector: &Vector,
+        n: usize,
+    ) -> Result<(Vec<SearchResult>, SearchStats), Box<dyn Error>> {
+        let start = Instant::now();
+        let vector = &self.config.prepare_vector(vector)?;
+        let ef = self.config.ef_search;
+        let mut stats = SearchStats::default();
+        let nearest = self.search_candidates_stats(vector, ef, &mut stats)?;
+        let nearest = nearest.into_iter().take(n).collect();
+        let results = self.to_results(nearest)?;
+        stats.total_time = start.elapsed();
+        Ok((results, stats))
+    }
 
-        let map_result = |candidate: Candidate| {
-            let id = candidate.vector_id.0;
-            let distance = candidate.distance.0;
-            let data = self.data[&candidate.vector_id].clone();
-            SearchResult { id, distance, data }
-        };
+    /// Returns the search counters aggregated since the collection
+    /// is loaded or the counters are reset. The counters include all
+    /// searches of the index, for example, the MMR and recommendation
+    /// searches, to be exported to a metrics system.
+    pub fn search_counters(&self) -> SearchCounters {
+        self.counters.snapshot()
+    }
 
-        Ok(search.iter().map(map_result).take(n).collect())
+    /// Resets the aggregated search counters to zero.
+    pub fn reset_search_counters(&self) {
+        self.counters.reset();
     }
 
-    /// Searches the collection for the true nearest neighbors.
+    /// Searches the collection for the nearest neighbors without
+    /// loading the metadata. Returns the IDs and distances.
     /// * `vector`: Vector to search.
     /// * `n`: Number of neighbors to return.
-    pub fn true_search(
+    pub fn search_ids(
+        &self,
+        vector: &Vector,
+        n: usize,
+    ) -> Result<Vec<(VectorID, f32)>, Box<dyn Error>> {
+        let vector = &self.config.prepare_vector(vector)?;
+        let nearest = self.search_candidates(vector, self.config.ef_search)?;
+        Ok(nearest.into_iter().take(n).collect())
+    }
+
+    /// Searches the collection for the nearest neighbors and re-ranks
+    /// them with the maximal marginal relevance to diversify the
+    /// results. Each next result is the candidate with the best
+    /// balance between its distance to the query and its distance to
+    /// the results selected before it.
+    /// * `vector`: Vector to search.
+    /// * `n`: Number of neighbors to return.
+    /// * `fetch_k`: Number of candidates to re-rank.
+    /// * `lambda`: Weight of the relevance between 0 and 1.
+    ///   1 ranks by the relevance only and 0 by the diversity only.
+    pub fn search_mmr(
         &self,
         vector: &Vector,
         n: usize,
+        fetch_k: usize,
+        lambda: f32,
     ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
-        let mut nearest = Vec::with_capacity(self.vectors.len());
+        if !(0.0..=1.0).contains(&lambda) {
+            return Err("The lambda must be between 0 and 1.".into());
+        }
+
+        let vector = &self.config.prepare_vector(vector)?;
+        let ef = max(fetch_k, self.config.ef_search);
+        let mut candidates = self.search_candidates(vector, ef)?;
+        candidates.truncate(max(fetch_k, n));
+
+        // Distance from each candidate to its nearest selected result.
+        let mut diversity = vec![f32::INFINITY; candidates.len()];
+        let mut selected = Vec::with_capacity(n);
+
+        while selected.len() < n && !candidates.is_empty() {
+            let score = |i: usize| {
+                let relevance = -candidates[i].1;
+                // The first result is only ranked by the relevance.
+                let diversity = match selected.is_empty() {
+                    true => 0.0,
+                    false => diversity[i],
+                };
+
+                lambda * relevance + (1.0 - lambda) * diversity
+            };
+
+            let best = (0..candidates.len())
+                .max_by(|a, b| score(*a).total_cmp(&score(*b)))
+                .unwrap_or_default();
 
-        // Calculate the distance between the query and each record.
-        // Then, create a search result for each record.
-        for (id, vec) in self.vectors.iter() {
-            let distance = vector.distance(vec);
-            let data = self.data[id].clone();
-            let res = SearchResult { id: id.0, distance, data };
-            nearest.push(res);
+            let (id, distance) = candidates.swap_remove(best);
+            diversity.swap_remove(best);
+
+            for (i, (other, _)) in candidates.iter().enumerate() {
+                let distance =
+                    euclidean(&self.vectors[&id], &self.vectors[other]);
+                diversity[i] = diversity[i].min(distance);
+            }
+
+            selected.push((id, distance));
         }
 
-        // Sort the nearest neighbors by distance.
-        nearest.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap());
-        nearest.truncate(n);
-        Ok(nearest)
+        self.to_results(selected)
     }
 
-    /// Returns the configured vector dimension of the collection.
-    pub fn dimension(&self) -> usize {
-        self.dimension
+    /// Recommends the records similar to the positive records and
+    /// dissimilar to the negative records using their stored vectors
+    /// and the average strategy. The given records are excluded.
+    /// * `positive`: IDs of the records to find similar records to.
+    /// * `negative`: IDs of the records to avoid.
+    /// * `n`: Number of records to return.
+    pub fn recommend(
+        &self,
+        positive: &[VectorID],
+        negative: &[VectorID],
+        n: usize,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+        let strategy = RecommendStrategy::Average;
+        self.recommend_with_strategy(positive, negative, n, strategy)
     }
 
-    /// Sets the vector dimension of the collection.
-    /// * `dimension`: New vector dimension.
-    pub fn set_dimension(
-        &mut self,
-        dimension: usize,
-    ) -> Result<(), Box<dyn Error>> {
-        // This can only be set if the collection is empty.
-        if !self.vectors.is_empty() {
-            return Err("The collection must be empty.".into());
+    /// Recommends the records similar to the positive records and
+    /// dissimilar to the negative records using their stored vectors.
+    /// The given records are excluded from the results.
+    /// * `positive`: IDs of the records to find similar records to.
+    /// * `negative`: IDs of the records to avoid.
+    /// * `n`: Number of records to return.
+    /// * `strategy`: Method to rank the records by the examples.
+    pub fn recommend_with_strategy(
+        &self,
+        positive: &[VectorID],
+        negative: &[VectorID],
+        n: usize,
+        strategy: RecommendStrategy,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+        if positive.is_empty() {
+            return Err("At least one positive record is required.".into());
         }
 
-        self.dimension = dimension;
-        Ok(())
-    }
+        let examples = positive.iter().chain(negative.iter());
+        if examples.clone().any(|id| !self.contains(id)) {
+            return Err(err::RECORD_NOT_FOUND.into());
+        }
 
-    /// Returns the number of vector records in the collection.
-    pub fn len(&self) -> usize {
-        self.count
-    }
+        // Extra candidates replace the examples found by the search.
+        let ef =
+            max(self.config.ef_search, n + positive.len() + negative.len());
+        let is_example = |id: &VectorID| examples.clone().any(|e| e == id);
+
+        let nearest = match strategy {
+            RecommendStrategy::Average => {
+                // Move the average of the positive vectors away from
+                // the average of the negative vectors.
+                let positive_mean = self.mean_vector(positive);
+                let query = match negative.is_empty() {
+                    true => positive_mean,
+                    false => {
+                        let negative_mean = self.mean_vector(negative);
+                        let iter = positive_mean.0.iter().zip(negative_mean.0);
+                        iter.map(|(p, n)| 2.0 * p - n)
+                            .collect::<Vec<_>>()
+                            .into()
+                    }
+                };
+
+                let query = self.config.prepare_vector(&query)?;
+                let mut nearest = self.search_candidates(&query, ef)?;
+                nearest.retain(|(id, _)| !is_example(id));
+                nearest
+            }
+            RecommendStrategy::BestScore => {
+                let mut candidates = vec![];
+                for id in positive.iter() {
+                    let vector = self.vectors[id].into();
+                    candidates.extend(self.search_candidates(&vector, ef)?);
+                }
 
-    /// Returns true if the collection is empty.
-    pub fn is_empty(&self) -> bool {
-        self.count == 0
-    }
+                let mut candidates: Vec<VectorID> =
+                    candidates.into_iter().map(|(id, _)| id).collect();
+                candidates.sort();
+                candidates.dedup();
+                candidates.retain(|id| !is_example(id));
+
+                // The distance to the nearest example of the kind.
+                let nearest_example = |id: &VectorID, examples: &[VectorID]| {
+                    let vector = &self.vectors[id];
+                    let iter = examples.iter();
+                    let distances =
+                        iter.map(|e| euclidean(vector, &self.vectors[e]));
+                    distances.fold(f32::INFINITY, f32::min)
+                };
+
+                // Candidates closer to a negative example rank last.
+                let mut scored: Vec<(bool, VectorID, f32)> = candidates
+                    .into_iter()
+                    .map(|id| {
+                        let distance = nearest_example(&id, positive);
+                        let is_negative =
+                            nearest_example(&id, negative) < distance;
+                        (is_negative, id, distance)
+                    })
+                    .collect();
+
+                scored.sort_by(|a, b| {
+                    a.0.cmp(&b.0).then(a.2.total_cmp(&b.2)).then(a.1.cmp(&b.1))
+                });
+
+                scored.into_iter().map(|(_, id, d)| (id, d)).collect()
+            }
+        };
 
-    /// Checks if the collection contains a vector ID.
-    /// * `id`: Vector ID to check.
-    pub fn contains(&self, id: &VectorID) -> bool {
-        self.vectors.contains_key(id)
+        self.to_results(nearest.into_iter().take(n).collect())
     }
 
-    /// Inserts a vector ID into the index layers.
-    fn insert_to_layers(&mut self, id: &VectorID) {
-        self.base_layer.push(BaseNode::default());
+    /// Returns the average of the stored vectors of the records.
+    fn mean_vector(&self, ids: &[VectorID]) -> Vector {
+        let mut mean = vec![0.0; self.dimension];
+        for id in ids.iter() {
+            for (sum, value) in mean.iter_mut().zip(self.vectors[id].iter()) {
+                *sum += value / ids.len() as f32;
+            }
+        }
+
+        mean.into()
+    }
+
+    /// Searches the collection for the nearest neighbors grouped by
+    /// the value of a metadata field. The search widens until it
+    /// finds the number of groups or visits the whole collection.
+    /// Records without the field are skipped. Returns no groups if
+    /// `groups` or `per_group` is zero.
+    /// * `vector`: Vector to search.
+    /// * `groups`: Number of groups to return.
+    /// * `per_group`: Maximum number of neighbors in each group.
+    /// * `field`: Metadata path of the group value like `document.id`.
+    pub fn search_grouped(
+        &self,
+        vector: &Vector,
+        groups: usize,
+        per_group: usize,
+        field: &str,
+    ) -> Result<Vec<SearchGroup>, Box<dyn Error>> {
+        let vector = &self.config.prepare_vector(vector)?;
+        if groups == 0 || per_group == 0 {
+            return Ok(vec![]);
+        }
+
+        let mut ef = max(self.config.ef_search, groups * per_group);
+
+        loop {
+            let candidates = self.search_candidates(vector, ef)?;
+            let mut result: Vec<SearchGroup> = vec![];
+
+            for (id, distance) in candidates.iter() {
+                let data = self.data.get(id)?;
+                let value = match data.get(field) {
+                    Some(value) => value.clone(),
+                    None => continue,
+                };
+
+                let search_result =
+                    SearchResult { id: id.0, distance: *distance, data };
+                match result.iter().position(|group| group.value == value) {
+                    Some(i) if result[i].results.len() < per_group => {
+                        result[i].results.push(search_result);
+                    }
+                    None if result.len() < groups => {
+                        let results = vec![search_result];
+                        result.push(SearchGroup { value, results });
+                    }
+                    _ => {}
+                }
+            }
+
+            // The candidates are limited by the collection size.
+            if result.len() >= groups || ef >= self.slots.len() {
+                return Ok(result);
+            }
+
+            ef = min(ef * 2, self.slots.len()).max(1);
+        }
+    }
+
+    /// Searches the nearest neighbor candidates of a prepared vector.
+    /// Returns up to `ef` IDs and distances sorted by the distance.
+    fn search_candidates(
+        &self,
+        vector: &Vector,
+        ef: usize,
+    ) -> Result<Vec<(VectorID, f32)>, Box<dyn Error>> {
+        let mut stats = SearchStats::default();
+        self.search_candidates_stats(vector, ef, &mut stats)
+    }
+
+    /// Searches the nearest neighbor candidates and writes the
+    /// statistics of the search which are added to the counters.
+    fn search_candidates_stats(
+        &self,
+        vector: &Vector,
+        ef: usize,
+        stats: &mut SearchStats,
+    ) -> Result<Vec<(VectorID, f32)>, Box<dyn Error>> {
+        let start = Instant::now();
+        stats.ef = ef;
+        let nearest = self.search_index(vector, ef, stats)?;
+        stats.search_time = start.elapsed();
+        stats.total_time = stats.search_time;
+        self.counters.record(stats);
+        Ok(nearest)
+    }
+
+    /// Searches the configured index and counts the work done.
+    fn search_index(
+        &self,
+        vector: &Vector,
+        ef: usize,
+        stats: &mut SearchStats,
+    ) -> Result<Vec<(VectorID, f32)>, Box<dyn Error>> {
+        if self.config.index == IndexType::Flat {
+            stats.distances = self.vectors.len();
+            return Ok(flat::search(&self.vectors, vector, ef));
+        }
+
+        if let (Some(ivf), IndexType::Ivf { probes, .. }) =
+            (&self.ivf, self.config.index)
+        {
+            let (nearest, distances) =
+                ivf.search_counted(vector, &self.vectors, ef, probes);
+            stats.distances = distances;
+            return Ok(nearest);
+        }
+
+        let mut search = Search::default();
+
+        if self.vectors.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // Find the first valid vector ID from the slots.
+        let slots_iter = self.slots.as_slice().into_par_iter();
+        let vector_id = match slots_iter.find_first(|id| id.is_valid()) {
+            Some(id) => id,
+            None => return Err("Unable to initiate search.".into()),
+        };
+
+        stats.entry_point = Some(vector_id.0);
+        search.visited.resize_capacity(self.slots.len());
+        search.push(vector_id, &vector.0, &self.vectors);
+
+        for layer in LayerID(self.upper_layers.len()).descend() {
+            search.ef = if layer.is_zero() { ef } else { 5 };
+            let expanded = search.expanded;
+
+            if layer.0 == 0 {
+                let layer = self.base_layer.as_slice();
+                search.search(layer, &vector.0, &self.vectors, M * 2);
+            } else {
+                let layer = self.upper_layers[layer.0 - 1].as_slice();
+                search.search(layer, &vector.0, &self.vectors, M);
+            }
+
+            stats.visited.push(search.expanded - expanded);
+            if !layer.is_zero() {
+                search.cull();
+            }
+        }
+
+        stats.distances = search.distances;
+
+        // The search compares the squared distances.
+        let map_result = |candidate: Candidate| {
+            (candidate.vector_id, candidate.distance.sqrt())
+        };
+
+        Ok(search.iter().map(map_result).collect())
+    }
+
+    /// Searches the collection for the true nearest neighbors.
+    /// * `vector`: Vector to search.
+    /// * `n`: Number of neighbors to return.
+    pub fn true_search(
+        &self,
+        vector: &Vector,
+        n: usize,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+        let vector = &self.config.prepare_vector(vector)?;
+
+        // Calculate the distance between the query and each record
+        // and only keep the nearest neighbors.
+        let nearest = flat::search(&self.vectors, vector, n);
+
+        // Only load the metadata of the nearest neighbors.
+        self.to_results(nearest)
+    }
+
+    /// Evaluates the search against the exact search of the true
+    /// nearest neighbors. Returns the recall@k with the throughput
+    /// and latency of the search to tune the configuration.
+    /// * `queries`: Vectors to search.
+    /// * `k`: Number of neighbors to compare.
+    pub fn evaluate_recall(
+        &self,
+        queries: &[Vector],
+        k: usize,
+    ) -> Result<RecallReport, Box<dyn Error>> {
+        if queries.is_empty() {
+            return Err(err::QUERIES_EMPTY.into());
+        }
+
+        let mut latencies = Vec::with_capacity(queries.len());
+        let (mut found, mut expected) = (0, 0);
+        for query in queries.iter() {
+            // Only the search being evaluated is timed.
+            let start = Instant::now();
+            let nearest = self.search_ids(query, k)?;
+            latencies.push(start.elapsed());
+
+            let vector = self.config.prepare_vector(query)?;
+            let truth = flat::search(&self.vectors, &vector, k);
+            let truth: Vec<VectorID> =
+                truth.iter().map(|(id, _)| *id).collect();
+            found +=
+                nearest.iter().filter(|(id, _)| truth.contains(id)).count();
+            expected += truth.len();
+        }
+
+        let seconds = latencies.iter().sum::<Duration>().as_secs_f32();
+        latencies.sort();
+        let percentile = |p: usize| latencies[(latencies.len() - 1) * p / 100];
+
+        Ok(RecallReport {
+            queries: queries.len(),
+            k,
+            recall: match expected {
+                0 => 1.0,
+                _ => found as f32 / expected as f32,
+            },
+            qps: queries.len() as f32 / seconds.max(f32::EPSILON),
+            p50: percentile(50),
+            p95: percentile(95),
+            p99: percentile(99),
+        })
+    }
+
+    /// Returns the vectors of randomly sampled records to use as the
+    /// queries when evaluating the search on the collection data.
+    /// * `size`: Number of vectors to sample.
+    pub fn sample_vectors(&self, size: usize) -> Vec<Vector> {
+        let ids: Vec<VectorID> =
+            self.vectors.iter().map(|(id, _)| id).collect();
+        let sample = ids.choose_multiple(&mut rand::thread_rng(), size);
+        sample.map(|id| self.vectors[id].into()).collect()
+    }
+
+    /// Tunes `ef_search` of the configuration to the smallest value
+    /// that reaches the target recall@k on the queries sampled from
+    /// the collection. The value is found with a binary search after
+    /// doubling it until the target is reached. If the target can't
+    /// be reached, the value with the best recall is used instead.
+    /// Save the collection to keep the tuned value in the database.
+    /// * `target_recall`: Recall to reach between 0 and 1.
+    /// * `k`: Number of neighbors to compare.
+    /// * `sample_size`: Number of vectors to sample as the queries.
+    pub fn tune(
+        &mut self,
+        target_recall: f32,
+        k: usize,
+        sample_size: usize,
+    ) -> Result<TuneReport, Box<dyn Error>> {
+        if self.config.index != IndexType::Hnsw {
+            return Err(err::INDEX_TYPE_UNSUPPORTED.into());
+        }
+
+        if !(0.0..=1.0).contains(&target_recall) {
+            return Err(err::TARGET_RECALL_INVALID.into());
+        }
+
+        let original = self.config.ef_search;
+        let queries = self.sample_vectors(sample_size);
+        let result = self.tune_ef_search(target_recall, k, &queries);
+        self.config.ef_search = match &result {
+            Ok(report) => report.ef_search,
+            Err(_) => original,
+        };
+
+        result
+    }
+
+    fn tune_ef_search(
+        &mut self,
+        target_recall: f32,
+        k: usize,
+        queries: &[Vector],
+    ) -> Result<TuneReport, Box<dyn Error>> {
+        let mut curve = vec![];
+        let mut evaluate = |collection: &mut Self, ef_search| {
+            collection.config.ef_search = ef_search;
+            let report = collection.evaluate_recall(queries, k)?;
+            let reached = report.recall >= target_recall;
+            curve.push(TunePoint { ef_search, report });
+            Ok::<bool, Box<dyn Error>>(reached)
+        };
+
+        // The search can't return more neighbors than the ef value
+        // and it covers the whole graph when it's the collection size.
+        let max = self.len().max(k).max(1);
+        let mut low = k.max(1);
+        let mut high = low;
+        let mut reached = evaluate(self, high)?;
+        while !reached && high < max {
+            low = high + 1;
+            high = (high * 2).min(max);
+            reached = evaluate(self, high)?;
+        }
+
+        // The target is reached at the high value if anywhere.
+        while reached && low < high {
+            let mid = (low + high) / 2;
+            match evaluate(self, mid)? {
+                true => high = mid,
+                false => low = mid + 1,
+            }
+        }
+
+        curve.sort_by_key(|point| point.ef_search);
+        let best = match reached {
+            true => curve.iter().find(|point| point.ef_search == high),
+            false => curve
+                .iter()
+                .max_by(|a, b| a.report.recall.total_cmp(&b.report.recall)),
+        };
+
+        // The curve has at least one point after the first evaluation.
+        let best = best.unwrap();
+        let (ef_search, recall) = (best.ef_search, best.report.recall);
+        Ok(TuneReport { ef_search, recall, curve })
+    }
+
+    /// Searches the configured text field for the records matching
+    /// the terms of the query ranked by their BM25 scores.
+    /// * `query`: Text to search.
+    /// * `n`: Number of records to return.
+    pub fn text_search(
+        &self,
+        query: &str,
+        n: usize,
+    ) -> Result<Vec<ScoredResult>, Box<dyn Error>> {
+        let matches = self.text_index()?.search(query, n);
+        self.to_scored_results(matches)
+    }
+
+    /// Searches the collection for both the nearest neighbors and the
+    /// text matches and combines them into a single ranking.
+    /// * `vector`: Vector to search.
+    /// * `text`: Text to search in the configured text field.
+    /// * `n`: Number of records to return.
+    /// * `fusion`: Method to combine the results.
+    pub fn hybrid_search(
+        &self,
+        vector: &Vector,
+        text: &str,
+        n: usize,
+        fusion: &Fusion,
+    ) -> Result<Vec<ScoredResult>, Box<dyn Error>> {
+        fusion.validate()?;
+
+        // More candidates than needed are fetched from both searches
+        // since a record might rank low in one and high in the other.
+        let candidates = max(n, self.config.ef_search);
+        let matches = self.text_index()?.search(text, candidates);
+        let nearest = self.search_ids(vector, candidates)?;
+
+        let mut fused = text::fuse(&nearest, &matches, fusion);
+        fused.truncate(n);
+        self.to_scored_results(fused)
+    }
+
+    /// Searches a named vector field for the nearest records. The
+    /// distance of a record is the distance of its nearest vector.
+    /// * `field`: Name of the vector field.
+    /// * `vector`: Vector to search.
+    /// * `n`: Number of records to return.
+    pub fn search_field(
+        &self,
+        field: &str,
+        vector: &Vector,
+        n: usize,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+        let queries =
+            HashMap::from([(field.to_string(), vec![vector.clone()])]);
+        self.search_fields(&queries, n, Aggregation::MaxSim)
+    }
+
+    /// Searches the named vector fields with the query vectors of the
+    /// fields and returns a result per record with the aggregated
+    /// distance. Records without vectors in a queried field are
+    /// excluded.
+    /// * `queries`: Query vectors by their field name.
+    /// * `n`: Number of records to return.
+    /// * `aggregation`: Method to combine the distances.
+    pub fn search_fields(
+        &self,
+        queries: &HashMap<String, Vec<Vector>>,
+        n: usize,
+        aggregation: Aggregation,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+        let mut prepared = HashMap::with_capacity(queries.len());
+        for (name, vectors) in queries.iter() {
+            let vectors = vectors.iter().map(|v| self.config.prepare_vector(v));
+            prepared.insert(name.clone(), vectors.collect::<Result<_, _>>()?);
+        }
+
+        let nearest = self.fields.search(&prepared, n, aggregation)?;
+        self.to_results(nearest)
+    }
+
+    /// Searches the sparse vectors of the records with the highest
+    /// dot products with the query. Records without a sparse vector
+    /// or without any shared dimension are excluded.
+    /// * `vector`: Sparse vector to search.
+    /// * `n`: Number of records to return.
+    pub fn sparse_search(
+        &self,
+        vector: &SparseVector,
+        n: usize,
+    ) -> Result<Vec<ScoredResult>, Box<dyn Error>> {
+        vector.validate()?;
+        let matches = self.sparse.search(vector, n);
+        self.to_scored_results(matches)
+    }
+
+    /// Searches the collection with both the dense and the sparse
+    /// vectors and combines the results into a single ranking.
+    /// * `vector`: Dense vector to search.
+    /// * `sparse`: Sparse vector to search.
+    /// * `n`: Number of records to return.
+    /// * `fusion`: Method to combine the results.
+    pub fn hybrid_sparse_search(
+        &self,
+        vector: &Vector,
+        sparse: &SparseVector,
+        n: usize,
+        fusion: &Fusion,
+    ) -> Result<Vec<ScoredResult>, Box<dyn Error>> {
+        sparse.validate()?;
+        fusion.validate()?;
+        let candidates = max(n, self.config.ef_search);
+        let matches = self.sparse.search(sparse, candidates);
+        let nearest = self.search_ids(vector, candidates)?;
+
+        let mut fused = text::fuse(&nearest, &matches, fusion);
+        fused.truncate(n);
+        self.to_scored_results(fused)
+    }
+
+    /// Returns the IDs of the records matching the metadata filter
+    /// in ascending order. The filtered paths must be indexed.
+    /// * `filter`: Filter of the metadata values.
+    pub fn find(
+        &self,
+        filter: &Filter,
+    ) -> Result<Vec<VectorID>, Box<dyn Error>> {
+        let mut ids = Vec::from_iter(filter.find_ids(&self.indexes)?);
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// Creates an index on a metadata path for the find method.
+    /// Existing records are indexed on creation.
+    /// * `path`: Metadata path like `author.name`.
+    /// * `kind`: Data structure of the index.
+    pub fn create_index(
+        &mut self,
+        path: &str,
+        kind: IndexKind,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.indexes.contains_key(path) {
+            return Err(err::INDEX_EXISTS.into());
+        }
+
+        let mut index = MetadataIndex::new(kind);
+        for (id, data) in self.data.entries()? {
+            if let Some(value) = data.get(path) {
+                index.insert(id, value);
+            }
+        }
+
+        self.indexes.insert(path.to_string(), index);
+        Ok(())
+    }
+
+    /// Removes the index on a metadata path.
+    /// * `path`: Metadata path of the index.
+    pub fn drop_index(&mut self, path: &str) -> Result<(), Box<dyn Error>> {
+        match self.indexes.remove(path) {
+            Some(_) => Ok(()),
+            None => Err(err::INDEX_NOT_FOUND.into()),
+        }
+    }
+
+    /// Returns the indexed metadata paths and their index kinds.
+    pub fn list_indexes(&self) -> Vec<(String, IndexKind)> {
+        let iter = self.indexes.iter();
+        iter.map(|(path, index)| (path.clone(), index.kind())).collect()
+    }
+
+    /// Returns an iterator over the records in ascending ID order.
+    /// The metadata of each record is loaded when it's reached.
+    pub fn iter(&self) -> RecordIter<'_> {
+        RecordIter { collection: self, position: 0 }
+    }
+
+    /// Returns all of the records in ascending ID order.
+    pub fn records(&self) -> Result<Vec<(VectorID, Record)>, Box<dyn Error>> {
+        self.iter().collect()
+    }
+
+    /// Returns a page of the records in ascending ID order starting
+    /// from the cursor. Since the IDs are never reused, the next
+    /// pages include the inserted records and skip the deleted ones.
+    /// * `cursor`: Vector ID to start from. None for the first page.
+    /// * `limit`: Maximum number of records in the page.
+    pub fn scan(
+        &self,
+        cursor: Option<VectorID>,
+        limit: usize,
+    ) -> Result<ScanPage, Box<dyn Error>> {
+        let position = cursor.map_or(0, |id| id.0 as usize);
+        let mut iter = RecordIter { collection: self, position };
+        let records = iter.by_ref().take(limit).collect::<Result<_, _>>()?;
+
+        // The cursor of the next page is the next stored record.
+        let slots = self.slots.iter().skip(iter.position);
+        let next = slots.copied().find(|id| self.contains(id));
+        Ok(ScanPage { records, next })
+    }
+
+    /// Returns the configured vector dimension of the collection.
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Sets the vector dimension of the collection.
+    /// * `dimension`: New vector dimension.
+    pub fn set_dimension(
+        &mut self,
+        dimension: usize,
+    ) -> Result<(), Box<dyn Error>> {
+        // This can only be set if the collection is empty.
+        if !self.vectors.is_empty() {
+            return Err("The collection must be empty.".into());
+        }
+
+        self.dimension = dimension;
+        Ok(())
+    }
+
+    /// Returns the number of vector records in the collection.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns true if the collection is empty.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Checks if the collection contains a vector ID.
+    /// * `id`: Vector ID to check.
+    pub fn contains(&self, id: &VectorID) -> bool {
+        self.vectors.contains(id)
+    }
+
+    /// Returns the values of the vector stored in the collection.
+    /// * `id`: Vector ID to retrieve.
+    pub fn get_vector(&self, id: &VectorID) -> Option<&[f32]> {
+        self.vectors.get(id)
+    }
+
+    /// Creates search results by loading the metadata of the IDs.
+    fn to_results(
+        &self,
+        nearest: Vec<(VectorID, f32)>,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+        let map_result = |(id, distance): (VectorID, f32)| {
+            let data = self.data.get(&id)?;
+            Ok(SearchResult { id: id.0, distance, data })
+        };
+
+        nearest.into_iter().map(map_result).collect()
+    }
+
+    /// Creates scored results by loading the metadata of the IDs.
+    fn to_scored_results(
+        &self,
+        scores: Vec<(VectorID, f32)>,
+    ) -> Result<Vec<ScoredResult>, Box<dyn Error>> {
+        let map_result = |(id, score): (VectorID, f32)| {
+            let data = self.data.get(&id)?;
+            Ok(ScoredResult { id: id.0, score, data })
+        };
+
+        scores.into_iter().map(map_result).collect()
+    }
+
+    /// Returns the text index or an error if it's not configured.
+    fn text_index(&self) -> Result<&TextIndex, Box<dyn Error>> {
+        match &self.text_index {
+            Some(index) => Ok(index),
+            None => Err(err::TEXT_INDEX_NOT_FOUND.into()),
+        }
+    }
+
+    /// Serializes the collection without the metadata which is
+    /// stored separately in the database.
+    pub(crate) fn to_stored(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let stored = StoredCollection {
+            config: BaseConfig::from(&self.config),
+            data: HashMap::new(),
+            vectors: HashMap::new(),
+            slots: &self.slots,
+            base_layer: &self.base_layer,
+            upper_layers: &self.upper_layers,
+            count: self.count,
+            dimension: self.dimension,
+        };
+
+        Ok(bincode::serialize(&stored)?)
+    }
+
+    /// Serializes the vector arena which is stored separately from
+    /// the collection to keep its contiguous layout.
+    pub(crate) fn vectors_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(bincode::serialize(&self.vectors)?)
+    }
+
+    /// Loads the stored vector arena. The collections stored before
+    /// the arena keep their vectors in the collection itself which
+    /// are moved to the arena on the next save.
+    pub(crate) fn load_vectors(
+        &mut self,
+        value: Option<&[u8]>,
+    ) -> Result<(), Box<dyn Error>> {
+        if let Some(value) = value {
+            self.vectors = bincode::deserialize(value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Attaches the tree to load the metadata from on demand.
+    pub(crate) fn attach_data(&mut self, tree: Tree) {
+        self.data.attach(tree);
+    }
+
+    /// Detaches the loaded collections from a metadata tree before the
+    /// tree is dropped so that they keep the metadata they read.
+    pub(crate) fn detach_data(tree: &Tree) -> Result<(), Box<dyn Error>> {
+        DataStore::detach(tree, None)
+    }
+
+    /// Returns the metadata changes to write to the tree.
+    pub(crate) fn data_batch(
+        &self,
+        tree: &Tree,
+    ) -> Result<Batch, Box<dyn Error>> {
+        self.data.batch(tree)
+    }
+
+    /// Clears the metadata changes written to the tree.
+    pub(crate) fn commit_data(&self, tree: &Tree) {
+        self.data.commit(tree);
+    }
+
+    /// Loads the metadata indexes stored in the tree.
+    pub(crate) fn load_indexes(
+        &mut self,
+        tree: &Tree,
+    ) -> Result<(), Box<dyn Error>> {
+        for entry in tree.iter() {
+            let (key, value) = entry?;
+            let path = String::from_utf8(key.to_vec())?;
+            self.indexes.insert(path, bincode::deserialize(&value)?);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the changes to replace the indexes stored in the tree.
+    pub(crate) fn index_batch(
+        &self,
+        tree: &Tree,
+    ) -> Result<Batch, Box<dyn Error>> {
+        let mut batch = Batch::default();
+        for key in tree.iter().keys() {
+            batch.remove(key?);
+        }
+
+        for (path, index) in self.indexes.iter() {
+            batch.insert(path.as_bytes(), bincode::serialize(index)?);
+        }
+
+        Ok(batch)
+    }
+
+    /// Returns the serialized text index to store if it's configured.
+    pub(crate) fn text_index_bytes(
+        &self,
+    ) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+        match &self.text_index {
+            Some(index) => Ok(Some(bincode::serialize(index)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Loads the stored text index. If the text field is configured
+    /// but the index is not stored, it's rebuilt from the metadata.
+    pub(crate) fn load_text_index(
+        &mut self,
+        value: Option<&[u8]>,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.config.text_field.is_none() {
+            return Ok(());
+        }
+
+        let index = match value {
+            Some(value) => bincode::deserialize(value)?,
+            None => {
+                let mut index = TextIndex::default();
+                for (id, data) in self.data.entries()? {
+                    if let Some(text) = self.config.text(&data) {
+                        index.insert(id, text);
+                    }
+                }
+
+                index
+            }
+        };
+
+        self.text_index = Some(index);
+        Ok(())
+    }
+
+    /// Returns the serialized sparse vectors to store if any.
+    pub(crate) fn sparse_index_bytes(
+        &self,
+    ) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+        match self.sparse.is_empty() {
+            true => Ok(None),
+            false => Ok(Some(bincode::serialize(&self.sparse)?)),
+        }
+    }
+
+    /// Loads the stored sparse vectors if any.
+    pub(crate) fn load_sparse_index(
+        &mut self,
+        value: Option<&[u8]>,
+    ) -> Result<(), Box<dyn Error>> {
+        if let Some(value) = value {
+            self.sparse = bincode::deserialize(value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the serialized vector fields to store if configured.
+    pub(crate) fn fields_bytes(
+        &self,
+    ) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+        match self.config.vector_fields.is_empty() {
+            true => Ok(None),
+            false => Ok(Some(bincode::serialize(&self.fields)?)),
+        }
+    }
+
+    /// Loads the stored vector fields if any.
+    pub(crate) fn load_fields(
+        &mut self,
+        value: Option<&[u8]>,
+    ) -> Result<(), Box<dyn Error>> {
+        if let Some(value) = value {
+            self.fields = bincode::deserialize(value)?;
+            self.fields.configure(&self.config);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the serialized IVF index to store if configured.
+    pub(crate) fn ivf_index_bytes(
+        &self,
+    ) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+        match &self.ivf {
+            Some(ivf) => Ok(Some(bincode::serialize(ivf)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Loads the stored IVF index if it's configured. The index is
+    /// trained from the vectors if it's not stored yet.
+    pub(crate) fn load_ivf_index(
+        &mut self,
+        value: Option<&[u8]>,
+    ) -> Result<(), Box<dyn Error>> {
+        self.ivf = match (value, &self.config.index) {
+            (Some(value), IndexType::Ivf { .. }) => {
+                Some(bincode::deserialize(value)?)
+            }
+            _ => self.config.ivf_index(&self.vectors),
+        };
+
+        Ok(())
+    }
+
+    /// Attaches a write-ahead log to record the operations.
+    pub(crate) fn attach_wal(&mut self, wal: WriteAheadLog) {
+        self.wal = Some(wal);
+    }
+
+    /// Returns the attached write-ahead log if any.
+    pub(crate) fn wal(&self) -> Option<&WriteAheadLog> {
+        self.wal.as_ref()
+    }
+
+    /// Takes the ownership of the write-ahead log if attached.
+    /// Fails if another handle logs the operations of the collection.
+    pub(crate) fn own_wal(&self) -> Result<(), Box<dyn Error>> {
+        match &self.wal {
+            Some(wal) => wal.own(),
+            None => Ok(()),
+        }
+    }
+
+    /// Appends the operation to the write-ahead log if attached. Only
+    /// the operations applied without an error are logged so that the
+    /// replay doesn't fail on them.
+    fn write_ahead(&self, operation: Operation) -> Result<(), Box<dyn Error>> {
+        match &self.wal {
+            Some(wal) => wal.append(&operation),
+            None => Ok(()),
+        }
+    }
+
+    /// Checkpoints the collection if the log is full.
+    fn try_checkpoint(&self) -> Result<(), Box<dyn Error>> {
+        match &self.wal {
+            Some(wal) if wal.should_checkpoint() => wal.checkpoint(self),
+            _ => Ok(()),
+        }
+    }
+
+    /// Replaces the metadata of a record and updates the indexes.
+    fn replace_data(
+        &mut self,
+        id: &VectorID,
+        data: &Metadata,
+    ) -> Result<(), Box<dyn Error>> {
+        self.unindex_data(id)?;
+        self.data.insert(*id, data.clone());
+        self.index_data(id, data);
+        Ok(())
+    }
+
+    /// Adds the metadata of a record to the metadata and text indexes.
+    fn index_data(&mut self, id: &VectorID, data: &Metadata) {
+        for (path, index) in self.indexes.iter_mut() {
+            if let Some(value) = data.get(path) {
+                index.insert(*id, value);
+            }
+        }
+
+        if let (Some(index), Some(text)) =
+            (&mut self.text_index, self.config.text(data))
+        {
+            index.insert(*id, text);
+        }
+    }
+
+    /// Removes the current metadata of a record from the indexes.
+    fn unindex_data(&mut self, id: &VectorID) -> Result<(), Box<dyn Error>> {
+        if self.indexes.is_empty() && self.text_index.is_none() {
+            return Ok(());
+        }
+
+        let data = self.data.get(id)?;
+        for (path, index) in self.indexes.iter_mut() {
+            if let Some(value) = data.get(path) {
+                index.remove(id, value);
+            }
+        }
+
+        if let (Some(index), Some(text)) =
+            (&mut self.text_index, self.config.text(&data))
+        {
+            index.remove(id, text);
+        }
+
+        Ok(())
+    }
+
+    /// Adds a stored vector to the configured vector index.
+    fn insert_to_index(&mut self, id: &VectorID) {
+        // The flat index scans the vectors without an index.
+        match (&mut self.ivf, self.config.index) {
+            (Some(ivf), _) => {
+                ivf.insert(*id, &self.vectors[id]);
+
+                // Retrain the centroids on the vectors as they grow.
+                if ivf.should_retrain() {
+                    ivf.retrain(&self.vectors);
+                }
+            }
+            (None, IndexType::Hnsw) => self.insert_to_layers(id),
+            _ => {}
+        }
+    }
+
+    /// Removes a vector from the configured vector index.
+    fn delete_from_index(&mut self, id: &VectorID) {
+        match (&mut self.ivf, self.config.index) {
+            (Some(ivf), _) => ivf.remove(id),
+            (None, IndexType::Hnsw) => self.delete_from_layers(id),
+            _ => {}
+        }
+    }
+
+    /// Inserts a vector ID into the index layers.
+    fn insert_to_layers(&mut self, id: &VectorID) {
+        // Re-indexed vectors already have a node in the base layer.
+        if id.0 as usize == self.base_layer.len() {
+            self.base_layer.push(BaseNode::default());
+        }
 
         let base_layer = self
             .base_layer
@@ -510,7 +1948,7 @@ impl Collection {
 
         let state = IndexConstruction {
             base_layer: base_layer.as_slice(),
-            search_pool: SearchPool::new(self.vectors.len()),
+            search_pool: SearchPool::new(self.slots.len()),
             top_layer,
             vectors: &self.vectors,
             config: &self.config,
@@ -540,7 +1978,12 @@ impl Collection {
                 false => break,
             };
 
-            let node = &mut upper_layer[id.0 as usize];
+            // Only the vectors in the upper layer have a node.
+            let node = match upper_layer.get_mut(id.0 as usize) {
+                Some(node) => node,
+                None => continue,
+            };
+
             let index = node.0.par_iter().position_first(|x| *x == *id);
 
             if let Some(index) = index {
@@ -550,19 +1993,99 @@ impl Collection {
     }
 }
 
+/// The collection stored in the database. The metadata and vectors are
+/// stored in separate trees and their fields are left empty to keep
+/// the format of the stored collections. The fields must be in the same
+/// order as `Collection`.
+#[derive(Serialize)]
+struct StoredCollection<'a> {
+    config: BaseConfig,
+    data: HashMap<VectorID, Metadata>,
+    vectors: HashMap<VectorID, Vector>,
+    slots: &'a Vec<VectorID>,
+    base_layer: &'a Vec<BaseNode>,
+    upper_layers: &'a Vec<Vec<UpperNode>>,
+    count: usize,
+    dimension: usize,
+}
+
+/// An incremental collection operation recorded in the log.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum Operation {
+    /// A new record is inserted.
+    Insert(Record),
+    /// An existing record is replaced.
+    Update(VectorID, Record),
+    /// An existing record is removed.
+    Delete(VectorID),
+    /// The metadata of an existing record is replaced.
+    UpdateData(VectorID, Metadata),
+}
+
 /// A record containing a vector and its associated data.
+///
+/// The record is non-exhaustive so that the optional embeddings can
+/// be added without breaking the code using it. Create it with `new`
+/// and set the optional embeddings with the builder methods.
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[non_exhaustive]
 pub struct Record {
     /// The vector embedding.
     pub vector: Vector,
     /// Data associated with the vector.
     pub data: Metadata,
+    /// Optional sparse vector embedding.
+    #[serde(default)]
+    pub sparse: Option<SparseVector>,
+    /// Vectors of the named vector fields. A field can have multiple
+    /// vectors like the token embeddings for the late interaction.
+    #[serde(default)]
+    pub vectors: HashMap<String, Vec<Vector>>,
 }
 
 impl Record {
     /// Creates a new record with a vector and data.
     pub fn new(vector: &Vector, data: &Metadata) -> Self {
-        Self { vector: vector.clone(), data: data.clone() }
+        Self {
+            vector: vector.clone(),
+            data: data.clone(),
+            sparse: None,
+            vectors: HashMap::new(),
+        }
+    }
+
+    /// Adds a vector to a named vector field of the record.
+    /// * `field`: Name of the vector field.
+    /// * `vector`: Vector embedding of the field.
+    pub fn with_vector(mut self, field: &str, vector: &Vector) -> Self {
+        let vectors = self.vectors.entry(field.to_string()).or_default();
+        vectors.push(vector.clone());
+        self
+    }
+
+    /// Adds multiple vectors to a named vector field of the record
+    /// like the token embeddings for the late interaction.
+    /// * `field`: Name of the vector field.
+    /// * `vectors`: Vector embeddings of the field.
+    pub fn with_vectors(mut self, field: &str, vectors: &[Vector]) -> Self {
+        let entry = self.vectors.entry(field.to_string()).or_default();
+        entry.extend_from_slice(vectors);
+        self
+    }
+
+    /// Sets the sparse vector of the record.
+    /// * `sparse`: Sparse vector embedding.
+    pub fn with_sparse(mut self, sparse: &SparseVector) -> Self {
+        self.sparse = Some(sparse.clone());
+        self
+    }
+
+    /// Returns an error if the sparse vector has non-finite values.
+    pub(crate) fn validate_sparse(&self) -> Result<(), NonFiniteVectorError> {
+        match &self.sparse {
+            Some(sparse) => sparse.validate(),
+            None => Ok(()),
+        }
     }
 
     /// Generates a random record for testing.
@@ -581,6 +2104,186 @@ impl Record {
     }
 }
 
+/// The method to rank the records by the recommendation examples.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecommendStrategy {
+    /// Searches with the average of the positive vectors moved away
+    /// from the average of the negative vectors. This takes a single
+    /// search regardless of the number of examples.
+    Average,
+    /// Searches near each of the positive vectors and ranks the
+    /// candidates by the distance to their nearest positive example.
+    /// Candidates closer to a negative example rank last.
+    BestScore,
+}
+
+/// The iterator over the records of a collection in ID order.
+pub struct RecordIter<'a> {
+    collection: &'a Collection,
+    position: usize,
+}
+
+impl<'a> Iterator for RecordIter<'a> {
+    type Item = Result<(VectorID, Record), Box<dyn Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let slots = &self.collection.slots;
+        while self.position < slots.len() {
+            let id = slots[self.position];
+            self.position += 1;
+
+            if self.collection.contains(&id) {
+                return Some(self.collection.get(&id).map(|r| (id, r)));
+            }
+        }
+
+        None
+    }
+}
+
+/// The page of the records returned by the collection scan.
+#[derive(Debug)]
+pub struct ScanPage {
+    /// Vector IDs and records in the page.
+    pub records: Vec<(VectorID, Record)>,
+    /// Cursor of the next page. None if it's the last page.
+    pub next: Option<VectorID>,
+}
+
+/// The group of the nearest neighbors sharing a metadata value.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SearchGroup {
+    /// Metadata value of the group.
+    pub value: Metadata,
+    /// Nearest neighbors in the group sorted by the distance.
+    pub results: Vec<SearchResult>,
+}
+
+/// The statistics of a search returned by `search_explain`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SearchStats {
+    /// Number of distances computed to the collection vectors.
+    pub distances: usize,
+    /// Number of nodes expanded in each HNSW layer from the top layer
+    /// to the base layer. Empty for the flat and IVF indexes.
+    pub visited: Vec<usize>,
+    /// Vector ID the graph search started from.
+    pub entry_point: Option<u32>,
+    /// Number of candidates kept by the search of the base layer.
+    pub ef: usize,
+    /// Time spent searching the index.
+    pub search_time: Duration,
+    /// Time spent including the preparation and metadata loading.
+    pub total_time: Duration,
+}
+
+/// The search counters aggregated over the searches of a collection.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+pub struct SearchCounters {
+    /// Number of searches of the index.
+    pub searches: u64,
+    /// Number of distances computed by the searches.
+    pub distances: u64,
+    /// Number of HNSW nodes expanded by the searches.
+    pub visited: u64,
+    /// Time spent searching the index.
+    pub search_time: Duration,
+}
+
+/// The search counters updated by the searches that only borrow the
+/// collection so they're atomic.
+#[derive(Default)]
+pub(crate) struct SearchMetrics {
+    searches: AtomicU64,
+    distances: AtomicU64,
+    visited: AtomicU64,
+    nanos: AtomicU64,
+}
+
+impl SearchMetrics {
+    /// Adds the statistics of a search to the counters.
+    pub fn record(&self, stats: &SearchStats) {
+        let visited = stats.visited.iter().sum::<usize>() as u64;
+        let nanos = stats.search_time.as_nanos() as u64;
+        let order = AtomicOrdering::Relaxed;
+        self.searches.fetch_add(1, order);
+        self.distances.fetch_add(stats.distances as u64, order);
+        self.visited.fetch_add(visited, order);
+        self.nanos.fetch_add(nanos, order);
+    }
+
+    /// Returns the current values of the counters.
+    pub fn snapshot(&self) -> SearchCounters {
+        let order = AtomicOrdering::Relaxed;
+        SearchCounters {
+            searches: self.searches.load(order),
+            distances: self.distances.load(order),
+            visited: self.visited.load(order),
+            search_time: Duration::from_nanos(self.nanos.load(order)),
+        }
+    }
+
+    /// Sets all counters to zero.
+    pub fn reset(&self) {
+        let order = AtomicOrdering::Relaxed;
+        self.searches.store(0, order);
+        self.distances.store(0, order);
+        self.visited.store(0, order);
+        self.nanos.store(0, order);
+    }
+}
+
+impl Clone for SearchMetrics {
+    fn clone(&self) -> Self {
+        let counters = self.snapshot();
+        Self {
+            searches: AtomicU64::new(counters.searches),
+            distances: AtomicU64::new(counters.distances),
+            visited: AtomicU64::new(counters.visited),
+            nanos: AtomicU64::new(counters.search_time.as_nanos() as u64),
+        }
+    }
+}
+
+/// The recall and speed of the search measured by `evaluate_recall`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RecallReport {
+    /// Number of the evaluated queries.
+    pub queries: usize,
+    /// Number of the nearest neighbors compared for each query.
+    pub k: usize,
+    /// Share of the true nearest neighbors found by the search.
+    pub recall: f32,
+    /// Number of queries searched per second one after another.
+    pub qps: f32,
+    /// Median latency of the search.
+    pub p50: Duration,
+    /// 95th percentile latency of the search.
+    pub p95: Duration,
+    /// 99th percentile latency of the search.
+    pub p99: Duration,
+}
+
+/// The result of tuning `ef_search` with `Collection::tune`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TuneReport {
+    /// Selected value of `ef_search` set to the configuration.
+    pub ef_search: usize,
+    /// Recall@k reached with the selected value.
+    pub recall: f32,
+    /// Evaluated values of `ef_search` in the ascending order.
+    pub curve: Vec<TunePoint>,
+}
+
+/// The recall and speed of the search with a value of `ef_search`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TunePoint {
+    /// Evaluated value of `ef_search`.
+    pub ef_search: usize,
+    /// Recall and speed of the search with the value.
+    pub report: RecallReport,
+}
+
 /// The collection nearest neighbor search result.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SearchResult {
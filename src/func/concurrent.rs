@@ -0,0 +1,603 @@
+use super::collection::*;
+use super::*;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+/// The thread-safe collection of vector records with HNSW indexing.
+///
+/// Unlike `Collection`, all operations take `&self` so the collection
+/// can be shared between threads, for example, using `Arc`. Searches
+/// run in parallel with each other and with the linking phase of
+/// inserts which only locks the graph nodes that it modifies.
+///
+/// Locks that are held at the same time are always acquired in the
+/// order of the fields below to prevent deadlocks between readers and
+/// writers. The write operations hold the slots or the vectors while
+/// they lock the other fields one at a time.
+///
+/// New vectors are linked in the base layer and the upper layers are
+/// rebuilt once the collection doubles in size since they were last
+/// built.
+///
+/// The metadata indexes, text index, and write-ahead log of a
+/// collection are carried over when it's converted so the operations
+/// keep them up to date. The log is checkpointed once it reaches the
+/// interval while the other write operations wait for it.
+pub struct ConcurrentCollection {
+    /// The collection configuration object.
+    pub config: Config,
+    // Private fields below.
+    // Held by the write operations and exclusively by the checkpoint.
+    writers: RwLock<()>,
+    slots: RwLock<Vec<VectorID>>,
+    vectors: RwLock<VectorArena>,
+    data: RwLock<DataStore>,
+    indexes: RwLock<HashMap<String, MetadataIndex>>,
+    text_index: RwLock<Option<TextIndex>>,
+    sparse: RwLock<SparseIndex>,
+    fields: RwLock<VectorFields>,
+    ivf: RwLock<Option<IvfIndex>>,
+    base_layer: RwLock<Vec<RwLock<BaseNode>>>,
+    upper_layers: RwLock<Vec<Vec<UpperNode>>>,
+    // Utility fields.
+    count: AtomicUsize,
+    layered: AtomicUsize,
+    dimension: AtomicUsize,
+    wal: Option<WriteAheadLog>,
+    counters: SearchMetrics,
+}
+
+impl ConcurrentCollection {
+    /// Creates an empty collection with the given configuration.
+    pub fn new(config: &Config) -> Self {
+        Collection::new(config).into()
+    }
+
+    /// Builds the collection index from vector records.
+    /// * `config`: Collection configuration.
+    /// * `records`: List of vectors to build the index from.
+    pub fn build(
+        config: &Config,
+        records: &[Record],
+    ) -> Result<Self, Box<dyn Error>> {
+        Ok(Collection::build(config, records)?.into())
+    }
+
+    /// Inserts a vector record into the collection.
+    /// Returns the vector ID assigned to the record.
+    /// * `record`: Vector record to insert.
+    pub fn insert(&self, record: &Record) -> Result<VectorID, Box<dyn Error>> {
+        let vector = self.config.prepare_vector(&record.vector)?;
+        record.validate_sparse()?;
+        self.fields.read().validate(&record.vectors)?;
+        self.config.validate(&record.data)?;
+        self.own_wal()?;
+
+        let writer = self.writers.read();
+        let id = {
+            let mut slots = self.slots.write();
+
+            // Ensure the number of records is within the limit.
+            if slots.len() == u32::MAX as usize {
+                return Err(err::COLLECTION_LIMIT.into());
+            }
+
+            // Ensure the vector dimension matches the collection.
+            // If it's the first record, set the dimension.
+            let dimension = self.dimension.load(AtomicOrdering::Acquire);
+            if self.count.load(AtomicOrdering::Acquire) == 0 && dimension == 0 {
+                let len = record.vector.len();
+                self.dimension.store(len, AtomicOrdering::Release);
+            } else if record.vector.len() != dimension {
+                let message = format!(
+                    "Invalid vector dimension. Expected dimension of {}.",
+                    dimension
+                );
+
+                return Err(message.into());
+            }
+
+            // Create a new vector ID using the next available slot.
+            let id: VectorID = slots.len().into();
+
+            self.vectors.write().insert(id, &vector.0);
+            self.data.write().insert(id, record.data.clone());
+            self.index_data(&id, &record.data);
+            if let Some(sparse) = &record.sparse {
+                self.sparse.write().insert(id, sparse);
+            }
+
+            self.fields.write().insert(id, &record.vectors)?;
+
+            // Register the new node before it's linked to the graph
+            // so that concurrent searches never see a dangling ID.
+            // The IVF index lists the vector instead of a graph node
+            // and the flat index only needs the vector.
+            match (self.ivf.write().as_mut(), self.config.index) {
+                (Some(ivf), _) => ivf.insert(id, &vector.0),
+                (None, IndexType::Hnsw) => {
+                    let node = RwLock::new(BaseNode::default());
+                    self.base_layer.write().push(node);
+                }
+                _ => {}
+            }
+
+            // Retrain the centroids on the vectors as they grow.
+            if self.ivf.read().as_ref().is_some_and(IvfIndex::should_retrain) {
+                let vectors = self.vectors.read();
+                if let Some(ivf) = self.ivf.write().as_mut() {
+                    ivf.retrain(&vectors);
+                }
+            }
+
+            slots.push(id);
+            self.count.fetch_add(1, AtomicOrdering::AcqRel);
+
+            // Log while the slots are locked so the replay assigns
+            // the same IDs in the same order.
+            self.write_ahead(Operation::Insert(record.clone()))?;
+            id
+        };
+
+        if self.config.index == IndexType::Hnsw {
+            self.insert_to_layers(&id);
+            self.rebuild_upper_layers();
+        }
+
+        drop(writer);
+        self.try_checkpoint()?;
+        Ok(id)
+    }
+
+    /// Deletes a vector record from the collection.
+    /// * `id`: Vector ID to delete.
+    pub fn delete(&self, id: &VectorID) -> Result<(), Box<dyn Error>> {
+        self.own_wal()?;
+        let writer = self.writers.read();
+        let mut slots = self.slots.write();
+        let mut vectors = self.vectors.write();
+
+        // Ensure the vector ID exists in the collection.
+        if !vectors.contains(id) {
+            return Err(err::RECORD_NOT_FOUND.into());
+        }
+
+        self.unindex_data(id)?;
+        vectors.remove(id);
+        self.data.write().remove(id);
+        self.sparse.write().remove(id);
+        self.fields.write().remove(id)?;
+        if let Some(ivf) = self.ivf.write().as_mut() {
+            ivf.remove(id);
+        }
+
+        slots[id.0 as usize] = INVALID;
+        self.count.fetch_sub(1, AtomicOrdering::AcqRel);
+        self.write_ahead(Operation::Delete(*id))?;
+
+        // Release the vectors before unlinking the node since the
+        // search skips vectors that are no longer stored.
+        drop(vectors);
+        drop(slots);
+
+        if self.config.index == IndexType::Hnsw {
+            self.delete_from_layers(id);
+        }
+
+        drop(writer);
+        self.try_checkpoint()
+    }
+
+    /// Updates a vector record in the collection.
+    /// * `id`: Vector ID to update.
+    /// * `record`: New vector record.
+    pub fn update(
+        &self,
+        id: &VectorID,
+        record: &Record,
+    ) -> Result<(), Box<dyn Error>> {
+        let vector = self.config.prepare_vector(&record.vector)?;
+        record.validate_sparse()?;
+        self.fields.read().validate(&record.vectors)?;
+        self.config.validate(&record.data)?;
+        self.own_wal()?;
+
+        let writer = self.writers.read();
+        {
+            let mut vectors = self.vectors.write();
+
+            if !vectors.contains(id) {
+                return Err(err::RECORD_NOT_FOUND.into());
+            }
+
+            let dimension = self.dimension.load(AtomicOrdering::Acquire);
+            if record.vector.len() != dimension {
+                let message = format!(
+                    "Invalid vector dimension. Expected dimension of {}.",
+                    dimension
+                );
+
+                return Err(message.into());
+            }
+
+            vectors.insert(*id, &vector.0);
+            self.unindex_data(id)?;
+            self.data.write().insert(*id, record.data.clone());
+            self.index_data(id, &record.data);
+
+            let mut sparse = self.sparse.write();
+            match &record.sparse {
+                Some(vector) => sparse.insert(*id, vector),
+                None => sparse.remove(id),
+            }
+
+            let mut fields = self.fields.write();
+            fields.remove(id)?;
+            fields.insert(*id, &record.vectors)?;
+            drop(fields);
+            drop(sparse);
+
+            if let Some(ivf) = self.ivf.write().as_mut() {
+                ivf.insert(*id, &vector.0);
+            }
+
+            self.write_ahead(Operation::Update(*id, record.clone()))?;
+        }
+
+        // Re-link the node using the updated vector.
+        if self.config.index == IndexType::Hnsw {
+            self.delete_from_layers(id);
+            self.insert_to_layers(id);
+        }
+
+        drop(writer);
+        self.try_checkpoint()
+    }
+
+    /// Returns the vector record associated with the ID.
+    /// * `id`: Vector ID to retrieve.
+    pub fn get(&self, id: &VectorID) -> Result<Record, Box<dyn Error>> {
+        let vectors = self.vectors.read();
+        let data = self.data.read();
+        let sparse = self.sparse.read();
+        let fields = self.fields.read();
+
+        match vectors.get(id) {
+            Some(vector) => Ok(Record {
+                vector: vector.into(),
+                data: data.get(id)?,
+                sparse: sparse.get(id).cloned(),
+                vectors: fields.get(id),
+            }),
+            None => Err(err::RECORD_NOT_FOUND.into()),
+        }
+    }
+
+    /// Searches the collection for the nearest neighbors.
+    /// * `vector`: Vector to search.
+    /// * `n`: Number of neighbors to return.
+    pub fn search(
+        &self,
+        vector: &Vector,
+        n: usize,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+        let vector = &self.config.prepare_vector(vector)?;
+        let slots = self.slots.read();
+        let vectors = self.vectors.read();
+        let data = self.data.read();
+        let ivf = self.ivf.read();
+        let base_layer = self.base_layer.read();
+        let upper_layers = self.upper_layers.read();
+
+        if vectors.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // The flat and IVF indexes return the nearest neighbors
+        // without traversing the graph.
+        let nearest = match (ivf.as_ref(), self.config.index) {
+            (_, IndexType::Flat) => Some(flat::search(&vectors, vector, n)),
+            (Some(ivf), IndexType::Ivf { probes, .. }) => {
+                Some(ivf.search(vector, vectors.deref(), n, probes))
+            }
+            _ => None,
+        };
+
+        if let Some(nearest) = nearest {
+            let to_result = |(id, distance): (VectorID, f32)| {
+                let data = data.get(&id)?;
+                Ok(SearchResult { id: id.0, distance, data })
+            };
+
+            return nearest.into_iter().map(to_result).collect();
+        }
+
+        // Find the first valid vector ID from the slots.
+        let slots_iter = slots.as_slice().into_par_iter();
+        let vector_id = match slots_iter.find_first(|id| id.is_valid()) {
+            Some(id) => id,
+            None => return Err("Unable to initiate search.".into()),
+        };
+
+        let mut search = Search::default();
+        search.visited.resize_capacity(slots.len());
+        search.push(vector_id, &vector.0, vectors.deref());
+
+        for layer in LayerID(upper_layers.len()).descend() {
+            search.ef = if layer.is_zero() { self.config.ef_search } else { 5 };
+
+            if layer.0 == 0 {
+                let layer = base_layer.as_slice();
+                search.search(layer, &vector.0, vectors.deref(), M * 2);
+            } else {
+                let layer = upper_layers[layer.0 - 1].as_slice();
+                search.search(layer, &vector.0, vectors.deref(), M);
+            }
+
+            if !layer.is_zero() {
+                search.cull();
+            }
+        }
+
+        let map_result = |candidate: Candidate| {
+            let id = candidate.vector_id.0;
+            let distance = candidate.distance.sqrt();
+            let data = data.get(&candidate.vector_id)?;
+            Ok(SearchResult { id, distance, data })
+        };
+
+        search.iter().map(map_result).take(n).collect()
+    }
+
+    /// Returns a copy of the collection that can be saved to the
+    /// database or used with the single-threaded APIs.
+    pub fn to_collection(&self) -> Collection {
+        self.copy(DataStore::clone)
+    }
+
+    /// Copies the collection with the metadata copied by `copy_data`.
+    fn copy(&self, copy_data: fn(&DataStore) -> DataStore) -> Collection {
+        let slots = self.slots.read();
+        let vectors = self.vectors.read();
+        let data = self.data.read();
+        let indexes = self.indexes.read();
+        let text_index = self.text_index.read();
+        let sparse = self.sparse.read();
+        let fields = self.fields.read();
+        let ivf = self.ivf.read();
+        let base_layer = self.base_layer.read();
+        let upper_layers = self.upper_layers.read();
+
+        Collection {
+            config: self.config.clone(),
+            data: copy_data(&data),
+            vectors: vectors.clone(),
+            slots: slots.clone(),
+            base_layer: base_layer.iter().map(|node| *node.read()).collect(),
+            upper_layers: upper_layers.clone(),
+            count: self.len(),
+            dimension: self.dimension(),
+            wal: None,
+            indexes: indexes.clone(),
+            text_index: text_index.clone(),
+            sparse: sparse.clone(),
+            fields: fields.clone(),
+            ivf: ivf.clone(),
+            counters: self.counters.clone(),
+        }
+    }
+
+    /// Returns the configured vector dimension of the collection.
+    pub fn dimension(&self) -> usize {
+        self.dimension.load(AtomicOrdering::Acquire)
+    }
+
+    /// Returns the number of vector records in the collection.
+    pub fn len(&self) -> usize {
+        self.count.load(AtomicOrdering::Acquire)
+    }
+
+    /// Returns true if the collection is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks if the collection contains a vector ID.
+    /// * `id`: Vector ID to check.
+    pub fn contains(&self, id: &VectorID) -> bool {
+        self.vectors.read().contains(id)
+    }
+
+    /// Links a registered vector ID to its neighbors in the base layer.
+    /// Only the nodes of the new vector and its neighbors are locked.
+    fn insert_to_layers(&self, id: &VectorID) {
+        let vectors = self.vectors.read();
+        let base_layer = self.base_layer.read();
+        let upper_layers = self.upper_layers.read();
+
+        // The vector might be deleted before it's linked.
+        if !vectors.contains(id) {
+            return;
+        }
+
+        let top_layer = LayerID(upper_layers.len());
+        let state = IndexConstruction {
+            base_layer: base_layer.as_slice(),
+            search_pool: SearchPool::new(base_layer.len()),
+            top_layer,
+            vectors: &vectors,
+            config: &self.config,
+        };
+
+        state.insert(id, &top_layer, &upper_layers);
+    }
+
+    /// Rebuilds the upper layers once the slots double since they
+    /// were last built. The new vectors are only linked in the base
+    /// layer so this keeps them reachable from the upper layers
+    /// while the cost of the rebuilds stays amortized.
+    fn rebuild_upper_layers(&self) {
+        let slots = self.slots.read().len();
+        let layered = self.layered.load(AtomicOrdering::Acquire);
+        if slots < max(layered, M) * 2 {
+            return;
+        }
+
+        // Only one of the inserting threads rebuilds the layers.
+        let order = (AtomicOrdering::AcqRel, AtomicOrdering::Acquire);
+        if self
+            .layered
+            .compare_exchange(layered, slots, order.0, order.1)
+            .is_err()
+        {
+            return;
+        }
+
+        let upper_layers = {
+            let vectors = self.vectors.read();
+            Collection::build_upper_layers(&self.config, &vectors, slots)
+        };
+
+        *self.upper_layers.write() = upper_layers;
+    }
+
+    /// Takes the ownership of the write-ahead log if attached.
+    fn own_wal(&self) -> Result<(), Box<dyn Error>> {
+        match &self.wal {
+            Some(wal) => wal.own(),
+            None => Ok(()),
+        }
+    }
+
+    /// Appends the operation to the write-ahead log if attached.
+    fn write_ahead(&self, operation: Operation) -> Result<(), Box<dyn Error>> {
+        match &self.wal {
+            Some(wal) => wal.append(&operation),
+            None => Ok(()),
+        }
+    }
+
+    /// Checkpoints the collection if the log is full. The write
+    /// operations are blocked so that the saved collection has all
+    /// of the logged operations and nothing else.
+    fn try_checkpoint(&self) -> Result<(), Box<dyn Error>> {
+        let wal = match &self.wal {
+            Some(wal) if wal.should_checkpoint() => wal,
+            _ => return Ok(()),
+        };
+
+        let _writers = self.writers.write();
+
+        // Another thread might have checkpointed the log already.
+        if !wal.should_checkpoint() {
+            return Ok(());
+        }
+
+        // The copy shares the metadata tree with this collection so
+        // saving it doesn't detach the metadata of this collection.
+        let collection = self.copy(DataStore::share);
+        wal.checkpoint(&collection)?;
+        self.data.read().commit_attached();
+        Ok(())
+    }
+
+    /// Adds the metadata of a record to the metadata and text indexes.
+    fn index_data(&self, id: &VectorID, data: &Metadata) {
+        for (path, index) in self.indexes.write().iter_mut() {
+            if let Some(value) = data.get(path) {
+                index.insert(*id, value);
+            }
+        }
+
+        if let (Some(index), Some(text)) =
+            (self.text_index.write().as_mut(), self.config.text(data))
+        {
+            index.insert(*id, text);
+        }
+    }
+
+    /// Removes the current metadata of a record from the indexes.
+    fn unindex_data(&self, id: &VectorID) -> Result<(), Box<dyn Error>> {
+        if self.indexes.read().is_empty() && self.text_index.read().is_none() {
+            return Ok(());
+        }
+
+        // The metadata is read before the indexes are locked to keep
+        // the order of the locks.
+        let data = self.data.read().get(id)?;
+        for (path, index) in self.indexes.write().iter_mut() {
+            if let Some(value) = data.get(path) {
+                index.remove(id, value);
+            }
+        }
+
+        if let (Some(index), Some(text)) =
+            (self.text_index.write().as_mut(), self.config.text(&data))
+        {
+            index.remove(id, text);
+        }
+
+        Ok(())
+    }
+
+    /// Removes the outgoing links of a vector ID from the base layer.
+    /// Links pointing to a deleted vector are skipped by the search.
+    fn delete_from_layers(&self, id: &VectorID) {
+        let base_layer = self.base_layer.read();
+        *base_layer[id.0 as usize].write() = BaseNode::default();
+    }
+}
+
+impl From<Collection> for ConcurrentCollection {
+    fn from(collection: Collection) -> Self {
+        let base_iter = collection.base_layer.into_iter();
+        let base_layer = base_iter.map(RwLock::new).collect();
+
+        // The upper layers of the collection fit its current slots.
+        let layered = collection.slots.len();
+
+        Self {
+            config: collection.config,
+            writers: RwLock::new(()),
+            slots: RwLock::new(collection.slots),
+            vectors: RwLock::new(collection.vectors),
+            data: RwLock::new(collection.data),
+            indexes: RwLock::new(collection.indexes),
+            text_index: RwLock::new(collection.text_index),
+            sparse: RwLock::new(collection.sparse),
+            fields: RwLock::new(collection.fields),
+            ivf: RwLock::new(collection.ivf),
+            base_layer: RwLock::new(base_layer),
+            upper_layers: RwLock::new(collection.upper_layers),
+            count: AtomicUsize::new(collection.count),
+            layered: AtomicUsize::new(layered),
+            dimension: AtomicUsize::new(collection.dimension),
+            wal: collection.wal,
+            counters: collection.counters,
+        }
+    }
+}
+
+impl From<ConcurrentCollection> for Collection {
+    fn from(collection: ConcurrentCollection) -> Self {
+        let base_iter = collection.base_layer.into_inner().into_iter();
+        let base_layer = base_iter.map(|node| node.into_inner()).collect();
+
+        Self {
+            config: collection.config,
+            data: collection.data.into_inner(),
+            vectors: collection.vectors.into_inner(),
+            slots: collection.slots.into_inner(),
+            base_layer,
+            upper_layers: collection.upper_layers.into_inner(),
+            count: collection.count.into_inner(),
+            dimension: collection.dimension.into_inner(),
+            wal: collection.wal,
+            indexes: collection.indexes.into_inner(),
+            text_index: collection.text_index.into_inner(),
+            sparse: collection.sparse.into_inner(),
+            fields: collection.fields.into_inner(),
+            ivf: collection.ivf.into_inner(),
+            counters: collection.counters,
+        }
+    }
+}
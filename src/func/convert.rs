@@ -0,0 +1,836 @@
+use super::*;
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use serde::ser::{self, Impossible};
+use std::fmt::{self, Display};
+
+/// Serializes a value into the metadata.
+pub fn to_metadata<T: Serialize + ?Sized>(
+    value: &T,
+) -> Result<Metadata, Box<dyn Error>> {
+    Ok(value.serialize(MetadataSerializer)?)
+}
+
+/// Deserializes a value from the metadata.
+pub fn from_metadata<T: DeserializeOwned>(
+    metadata: Metadata,
+) -> Result<T, Box<dyn Error>> {
+    Ok(T::deserialize(metadata)?)
+}
+
+/// The error when the value can't be converted to or from metadata.
+#[derive(Debug)]
+pub struct ConvertError(String);
+
+impl Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for ConvertError {}
+
+impl ser::Error for ConvertError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+impl de::Error for ConvertError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+// Serialization.
+// Structs and maps become objects, sequences and tuples become arrays,
+// and enum variants with data become objects with the variant as key.
+
+/// The serde serializer producing the metadata from any value.
+pub struct MetadataSerializer;
+
+impl ser::Serializer for MetadataSerializer {
+    type Ok = Metadata;
+    type Error = ConvertError;
+
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeArray;
+    type SerializeMap = SerializeObject;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeObject;
+
+    fn serialize_bool(self, v: bool) -> Result<Metadata, ConvertError> {
+        Ok(Metadata::Boolean(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Metadata, ConvertError> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Metadata, ConvertError> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Metadata, ConvertError> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Metadata, ConvertError> {
+        Ok(Metadata::Signed(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Metadata, ConvertError> {
+        match i64::try_from(v) {
+            Ok(v) => self.serialize_i64(v),
+            Err(_) => Err(ser::Error::custom("The integer is too large.")),
+        }
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Metadata, ConvertError> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Metadata, ConvertError> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Metadata, ConvertError> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Metadata, ConvertError> {
+        self.serialize_u128(v as u128)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Metadata, ConvertError> {
+        match usize::try_from(v) {
+            Ok(v) => Ok(Metadata::Integer(v)),
+            Err(_) => Err(ser::Error::custom("The integer is too large.")),
+        }
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Metadata, ConvertError> {
+        Ok(Metadata::Float(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Metadata, ConvertError> {
+        Ok(Metadata::Double(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Metadata, ConvertError> {
+        Ok(Metadata::Text(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Metadata, ConvertError> {
+        Ok(Metadata::Text(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Metadata, ConvertError> {
+        Ok(Metadata::Bytes(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Metadata, ConvertError> {
+        Ok(Metadata::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(
+        self,
+        value: &T,
+    ) -> Result<Metadata, ConvertError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Metadata, ConvertError> {
+        Ok(Metadata::Null)
+    }
+
+    fn serialize_unit_struct(
+        self,
+        _name: &'static str,
+    ) -> Result<Metadata, ConvertError> {
+        Ok(Metadata::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Metadata, ConvertError> {
+        Ok(Metadata::Text(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Metadata, ConvertError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Metadata, ConvertError> {
+        let value = value.serialize(self)?;
+        let obj = HashMap::from([(variant.to_string(), value)]);
+        Ok(Metadata::Object(obj))
+    }
+
+    fn serialize_seq(
+        self,
+        len: Option<usize>,
+    ) -> Result<SerializeArray, ConvertError> {
+        let items = Vec::with_capacity(len.unwrap_or(0));
+        Ok(SerializeArray { items, variant: None })
+    }
+
+    fn serialize_tuple(
+        self,
+        len: usize,
+    ) -> Result<SerializeArray, ConvertError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, ConvertError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, ConvertError> {
+        let items = Vec::with_capacity(len);
+        Ok(SerializeArray { items, variant: Some(variant) })
+    }
+
+    fn serialize_map(
+        self,
+        _len: Option<usize>,
+    ) -> Result<SerializeObject, ConvertError> {
+        Ok(SerializeObject::default())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<SerializeObject, ConvertError> {
+        Ok(SerializeObject::default())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeObject, ConvertError> {
+        let variant = Some(variant);
+        Ok(SerializeObject { variant, ..Default::default() })
+    }
+}
+
+/// Wraps the value in an object if it's the data of an enum variant.
+fn wrap_variant(variant: Option<&str>, value: Metadata) -> Metadata {
+    match variant {
+        Some(variant) => {
+            let obj = HashMap::from([(variant.to_string(), value)]);
+            Metadata::Object(obj)
+        }
+        None => value,
+    }
+}
+
+/// Serializer of the sequences, tuples, and tuple variants.
+pub struct SerializeArray {
+    items: Vec<Metadata>,
+    variant: Option<&'static str>,
+}
+
+impl SerializeArray {
+    fn push<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ConvertError> {
+        self.items.push(value.serialize(MetadataSerializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Metadata, ConvertError> {
+        Ok(wrap_variant(self.variant, Metadata::Array(self.items)))
+    }
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Metadata;
+    type Error = ConvertError;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ConvertError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Metadata, ConvertError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = Metadata;
+    type Error = ConvertError;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ConvertError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Metadata, ConvertError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = Metadata;
+    type Error = ConvertError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ConvertError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Metadata, ConvertError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeArray {
+    type Ok = Metadata;
+    type Error = ConvertError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ConvertError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Metadata, ConvertError> {
+        self.finish()
+    }
+}
+
+/// Serializer of the maps, structs, and struct variants.
+#[derive(Default)]
+pub struct SerializeObject {
+    obj: HashMap<String, Metadata>,
+    key: Option<String>,
+    variant: Option<&'static str>,
+}
+
+impl SerializeObject {
+    fn insert<T: Serialize + ?Sized>(
+        &mut self,
+        key: &str,
+        value: &T,
+    ) -> Result<(), ConvertError> {
+        let value = value.serialize(MetadataSerializer)?;
+        self.obj.insert(key.to_string(), value);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Metadata, ConvertError> {
+        Ok(wrap_variant(self.variant, Metadata::Object(self.obj)))
+    }
+}
+
+impl ser::SerializeMap for SerializeObject {
+    type Ok = Metadata;
+    type Error = ConvertError;
+
+    fn serialize_key<T: Serialize + ?Sized>(
+        &mut self,
+        key: &T,
+    ) -> Result<(), ConvertError> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ConvertError> {
+        let key = match self.key.take() {
+            Some(key) => key,
+            None => return Err(ser::Error::custom("The map key is missing.")),
+        };
+
+        self.insert(&key, value)
+    }
+
+    fn end(self) -> Result<Metadata, ConvertError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for SerializeObject {
+    type Ok = Metadata;
+    type Error = ConvertError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ConvertError> {
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Metadata, ConvertError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeObject {
+    type Ok = Metadata;
+    type Error = ConvertError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ConvertError> {
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Metadata, ConvertError> {
+        self.finish()
+    }
+}
+
+/// Serializer of the map keys since the object keys are strings.
+struct KeySerializer;
+
+impl KeySerializer {
+    fn invalid() -> ConvertError {
+        ser::Error::custom("The map key must be a string or a number.")
+    }
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = ConvertError;
+
+    type SerializeSeq = Impossible<String, ConvertError>;
+    type SerializeTuple = Impossible<String, ConvertError>;
+    type SerializeTupleStruct = Impossible<String, ConvertError>;
+    type SerializeTupleVariant = Impossible<String, ConvertError>;
+    type SerializeMap = Impossible<String, ConvertError>;
+    type SerializeStruct = Impossible<String, ConvertError>;
+    type SerializeStructVariant = Impossible<String, ConvertError>;
+
+    fn serialize_bool(self, v: bool) -> Result<String, ConvertError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<String, ConvertError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<String, ConvertError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<String, ConvertError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<String, ConvertError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<String, ConvertError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<String, ConvertError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<String, ConvertError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<String, ConvertError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String, ConvertError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<String, ConvertError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_char(self, v: char) -> Result<String, ConvertError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String, ConvertError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, ConvertError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_none(self) -> Result<String, ConvertError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(
+        self,
+        value: &T,
+    ) -> Result<String, ConvertError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<String, ConvertError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_unit_struct(
+        self,
+        _name: &'static str,
+    ) -> Result<String, ConvertError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<String, ConvertError> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, ConvertError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, ConvertError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_seq(
+        self,
+        _len: Option<usize>,
+    ) -> Result<Self::SerializeSeq, ConvertError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_tuple(
+        self,
+        _len: usize,
+    ) -> Result<Self::SerializeTuple, ConvertError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, ConvertError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, ConvertError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_map(
+        self,
+        _len: Option<usize>,
+    ) -> Result<Self::SerializeMap, ConvertError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, ConvertError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, ConvertError> {
+        Err(Self::invalid())
+    }
+}
+
+// Deserialization.
+// The metadata is self-describing so every type is deserialized based
+// on the metadata variant except for options and enums.
+
+impl<'de> de::Deserializer<'de> for Metadata {
+    type Error = ConvertError;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, ConvertError> {
+        match self {
+            Metadata::Text(v) => visitor.visit_string(v),
+            Metadata::Integer(v) => visitor.visit_u64(v as u64),
+            Metadata::Float(v) => visitor.visit_f32(v),
+            Metadata::Array(v) => {
+                let mut seq = SeqDeserializer::new(v.into_iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Metadata::Object(v) => {
+                let iter = v.into_iter().map(|(k, v)| (KeyDeserializer(k), v));
+                let mut map = MapDeserializer::new(iter);
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+            Metadata::Boolean(v) => visitor.visit_bool(v),
+            Metadata::Null => visitor.visit_unit(),
+            Metadata::Signed(v) => visitor.visit_i64(v),
+            Metadata::Double(v) => visitor.visit_f64(v),
+            Metadata::Bytes(v) => visitor.visit_byte_buf(v),
+            Metadata::Timestamp(v) => visitor.visit_i64(v),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, ConvertError> {
+        match self {
+            Metadata::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ConvertError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ConvertError> {
+        let (variant, value) = match self {
+            Metadata::Text(variant) => (variant, None),
+            Metadata::Object(obj) if obj.len() == 1 => {
+                let (variant, value) = obj.into_iter().next().unwrap();
+                (variant, Some(value))
+            }
+            _ => {
+                let message = "The enum must be a text or an object.";
+                return Err(de::Error::custom(message));
+            }
+        };
+
+        visitor.visit_enum(EnumDeserializer { variant, value })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str
+        string bytes byte_buf unit unit_struct seq tuple tuple_struct
+        map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, ConvertError> for Metadata {
+    type Deserializer = Self;
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+/// Deserializer of the object keys that parses the numeric keys
+/// since the serializer converts the map keys to strings.
+struct KeyDeserializer(String);
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident),*) => {
+        $(fn $method<V: Visitor<'de>>(
+            self,
+            visitor: V,
+        ) -> Result<V::Value, ConvertError> {
+            match self.0.parse() {
+                Ok(value) => visitor.$visit(value),
+                Err(_) => visitor.visit_string(self.0),
+            }
+        })*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for KeyDeserializer {
+    type Error = ConvertError;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, ConvertError> {
+        visitor.visit_string(self.0)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ConvertError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ConvertError> {
+        let key = Metadata::Text(self.0);
+        key.deserialize_enum(name, variants, visitor)
+    }
+
+    deserialize_parsed_key! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64
+    }
+
+    serde::forward_to_deserialize_any! {
+        i128 u128 f32 f64 char str string bytes byte_buf option unit
+        unit_struct seq tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, ConvertError> for KeyDeserializer {
+    type Deserializer = Self;
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    value: Option<Metadata>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+    type Error = ConvertError;
+    type Variant = VariantDeserializer;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), ConvertError> {
+        let variant = self.variant.into_deserializer();
+        let value = seed.deserialize(variant)?;
+        Ok((value, VariantDeserializer { value: self.value }))
+    }
+}
+
+struct VariantDeserializer {
+    value: Option<Metadata>,
+}
+
+impl VariantDeserializer {
+    fn value(self) -> Result<Metadata, ConvertError> {
+        let message = "The enum variant data is missing.";
+        self.value.ok_or_else(|| de::Error::custom(message))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for VariantDeserializer {
+    type Error = ConvertError;
+
+    fn unit_variant(self) -> Result<(), ConvertError> {
+        match self.value {
+            None | Some(Metadata::Null) => Ok(()),
+            Some(_) => Err(de::Error::custom("The enum variant has data.")),
+        }
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, ConvertError> {
+        seed.deserialize(self.value()?)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ConvertError> {
+        de::Deserializer::deserialize_seq(self.value()?, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ConvertError> {
+        de::Deserializer::deserialize_map(self.value()?, visitor)
+    }
+}
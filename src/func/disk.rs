@@ -0,0 +1,382 @@
+use super::collection::*;
+use super::*;
+use memmap2::Mmap;
+use std::fs::{rename, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::slice;
+
+/// Identifier at the start of the disk index file.
+const MAGIC: u32 = 0x5344_4249;
+/// Version of the disk index file layout.
+const VERSION: u32 = 1;
+/// Number of u32 words in the file header.
+const HEADER_LEN: usize = 9;
+
+/// The read-only collection with its index stored in a flat file.
+///
+/// The vectors and the graph nodes are memory-mapped and only paged in
+/// by the operating system when the search reads them. The metadata is
+/// stored in sled and only fetched for the final search results. This
+/// allows searching collections that are larger than the memory.
+/// The sparse vectors and the named vector fields of the records are
+/// not written to the disk.
+///
+/// The file consists of little-endian u32 words: the header, the upper
+/// layer lengths, the slots, the vectors, the base layer, and the upper
+/// layers from the lowest to the highest.
+pub struct DiskCollection {
+    /// The collection configuration object.
+    pub config: Config,
+    // Private fields below.
+    mmap: Mmap,
+    data: DataStore,
+    // Utility fields.
+    count: usize,
+    dimension: usize,
+    slots: usize,
+    upper_layers: Vec<(usize, usize)>,
+}
+
+impl DiskCollection {
+    /// Writes the collection index to a flat file and its metadata
+    /// to a tree replacing the previous content of the tree.
+    ///
+    /// The file is written to a temporary file first which replaces
+    /// the previous file when it's complete. The collections opened
+    /// from the previous file keep their memory map of it.
+    /// * `path`: Path of the index file.
+    /// * `data`: Tree to store the metadata of the records.
+    /// * `collection`: Collection to write.
+    pub(crate) fn write(
+        path: &Path,
+        data: &Tree,
+        collection: &Collection,
+    ) -> Result<(), Box<dyn Error>> {
+        // The file layout is the HNSW graph of the collection.
+        if collection.config.index != IndexType::Hnsw {
+            return Err(err::INDEX_TYPE_UNSUPPORTED.into());
+        }
+
+        let batch = collection.data.batch(data)?;
+        data.apply_batch(batch)?;
+
+        let temp_path = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+        let mut write = |word: u32| writer.write_all(&word.to_le_bytes());
+
+        let config = &collection.config;
+        let dimension = collection.dimension;
+        let slots = &collection.slots;
+        let upper_layers = &collection.upper_layers;
+
+        // Write the header and the upper layer lengths.
+        let header = [
+            MAGIC,
+            VERSION,
+            dimension as u32,
+            slots.len() as u32,
+            collection.count as u32,
+            upper_layers.len() as u32,
+            config.ef_construction as u32,
+            config.ef_search as u32,
+            config.ml.to_bits(),
+        ];
+
+        let lens = upper_layers.iter().map(|layer| layer.len() as u32);
+        for word in header.into_iter().chain(lens) {
+            write(word)?;
+        }
+
+        for id in slots.iter() {
+            write(id.0)?;
+        }
+
+        // Deleted vectors are zeroed to keep the stride uniform.
+        let empty = vec![0.0; dimension];
+        for id in slots.iter() {
+            let vector = match collection.vectors.get(id) {
+                Some(vector) => vector,
+                None => empty.as_slice(),
+            };
+
+            for value in vector.iter() {
+                write(value.to_bits())?;
+            }
+        }
+
+        // The file has exactly one base node for each slot.
+        let empty = BaseNode::default();
+        for i in 0..slots.len() {
+            let node = collection.base_layer.get(i).unwrap_or(&empty);
+            for id in node.0.iter() {
+                write(id.0)?;
+            }
+        }
+
+        for layer in upper_layers.iter() {
+            for node in layer.iter() {
+                for id in node.0.iter() {
+                    write(id.0)?;
+                }
+            }
+        }
+
+        // Sync the file before it replaces the previous file so
+        // that the file is never partially written.
+        writer.into_inner()?.sync_all()?;
+        rename(temp_path, path)?;
+        Ok(())
+    }
+
+    /// Opens the collection from an index file.
+    /// * `path`: Path of the index file.
+    /// * `data`: Tree storing the metadata of the records.
+    pub(crate) fn open(
+        path: &Path,
+        data: Tree,
+    ) -> Result<Self, Box<dyn Error>> {
+        if cfg!(target_endian = "big") {
+            return Err("Disk collections require a little-endian CPU.".into());
+        }
+
+        // The file is never modified in place after it's written since
+        // saving the collection again replaces it with a new file.
+        let file = File::open(path)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        let invalid = || "The disk collection file is invalid.".into();
+        if mmap.len() % 4 != 0 || mmap.len() < HEADER_LEN * 4 {
+            return Err(invalid());
+        }
+
+        let words = as_words(&mmap);
+        if words[0] != MAGIC || words[1] != VERSION {
+            return Err(invalid());
+        }
+
+        let dimension = words[2] as usize;
+        let slots = words[3] as usize;
+        let count = words[4] as usize;
+        let num_layers = words[5] as usize;
+
+        let config = Config {
+            ef_construction: words[6] as usize,
+            ef_search: words[7] as usize,
+            ml: f32::from_bits(words[8]),
+            ..Default::default()
+        };
+
+        // Find the offsets of the upper layers.
+        let lens = words.get(HEADER_LEN..HEADER_LEN + num_layers);
+        let lens = lens.ok_or_else(invalid)?;
+        let mut offset = HEADER_LEN + num_layers + slots * (1 + dimension);
+        offset += slots * M * 2;
+
+        let mut upper_layers = Vec::with_capacity(num_layers);
+        for len in lens.iter().map(|len| *len as usize) {
+            upper_layers.push((offset, len));
+            offset += len * M;
+        }
+
+        if offset != words.len() {
+            return Err(invalid());
+        }
+
+        let mut store = DataStore::default();
+        store.attach(data);
+
+        Ok(Self {
+            config,
+            mmap,
+            data: store,
+            count,
+            dimension,
+            slots,
+            upper_layers,
+        })
+    }
+
+    /// Searches the collection for the nearest neighbors.
+    /// * `vector`: Vector to search.
+    /// * `n`: Number of neighbors to return.
+    pub fn search(
+        &self,
+        vector: &Vector,
+        n: usize,
+    ) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+        // Only fetch the metadata of the final results.
+        let mut results = Vec::with_capacity(n);
+        for (id, distance) in self.search_ids(vector, n)? {
+            let data = self.data.get(&id)?;
+            results.push(SearchResult { id: id.0, distance, data });
+        }
+
+        Ok(results)
+    }
+
+    /// Searches the collection for the nearest neighbors without
+    /// loading the metadata. Returns the IDs and distances.
+    /// * `vector`: Vector to search.
+    /// * `n`: Number of neighbors to return.
+    pub fn search_ids(
+        &self,
+        vector: &Vector,
+        n: usize,
+    ) -> Result<Vec<(VectorID, f32)>, Box<dyn Error>> {
+        let vector = &self.config.prepare_vector(vector)?;
+        if self.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let vectors = self.vectors();
+
+        // Find the first valid vector ID from the slots.
+        let vector_id = match vectors.slots.iter().find(|id| id.is_valid()) {
+            Some(id) => id,
+            None => return Err("Unable to initiate search.".into()),
+        };
+
+        let mut search = Search::default();
+        search.visited.resize_capacity(self.slots);
+        search.push(vector_id, &vector.0, &vectors);
+
+        for layer in LayerID(self.upper_layers.len()).descend() {
+            search.ef = if layer.is_zero() { self.config.ef_search } else { 5 };
+
+            if layer.is_zero() {
+                let layer = self.base_layer();
+                search.search(layer, &vector.0, &vectors, M * 2);
+            } else {
+                let layer = self.upper_layer(layer.0 - 1);
+                search.search(layer, &vector.0, &vectors, M);
+                search.cull();
+            }
+        }
+
+        // The search compares the squared distances.
+        let map_result = |candidate: Candidate| {
+            (candidate.vector_id, candidate.distance.sqrt())
+        };
+
+        Ok(search.iter().map(map_result).take(n).collect())
+    }
+
+    /// Returns the vector record associated with the ID.
+    /// * `id`: Vector ID to retrieve.
+    pub fn get(&self, id: &VectorID) -> Result<Record, Box<dyn Error>> {
+        let vector = match self.vectors().get_vector(id) {
+            Some(vector) => Vector::from(vector.to_vec()),
+            None => return Err(err::RECORD_NOT_FOUND.into()),
+        };
+
+        let data = self.data.get(id)?;
+        let vectors = HashMap::new();
+        Ok(Record { vector, data, sparse: None, vectors })
+    }
+
+    /// Returns the configured vector dimension of the collection.
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Returns the number of vector records in the collection.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns true if the collection is empty.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Checks if the collection contains a vector ID.
+    /// * `id`: Vector ID to check.
+    pub fn contains(&self, id: &VectorID) -> bool {
+        self.vectors().get_vector(id).is_some()
+    }
+
+    fn vectors(&self) -> DiskVectors<'_> {
+        let words = as_words(&self.mmap);
+        let start = HEADER_LEN + self.upper_layers.len();
+        let slots = &words[start..start + self.slots];
+
+        let start = start + self.slots;
+        let values = &words[start..start + self.slots * self.dimension];
+
+        DiskVectors {
+            slots: as_ids(slots),
+            values: as_floats(values),
+            dimension: self.dimension,
+        }
+    }
+
+    fn base_layer(&self) -> DiskLayer<'_> {
+        let words = as_words(&self.mmap);
+        let start = HEADER_LEN + self.upper_layers.len();
+        let start = start + self.slots * (1 + self.dimension);
+        let nodes = &words[start..start + self.slots * M * 2];
+        DiskLayer { nodes: as_ids(nodes), width: M * 2 }
+    }
+
+    fn upper_layer(&self, index: usize) -> DiskLayer<'_> {
+        let words = as_words(&self.mmap);
+        let (start, len) = self.upper_layers[index];
+        let nodes = &words[start..start + len * M];
+        DiskLayer { nodes: as_ids(nodes), width: M }
+    }
+}
+
+/// The vectors stored contiguously in the memory-mapped file.
+struct DiskVectors<'a> {
+    slots: &'a [VectorID],
+    values: &'a [f32],
+    dimension: usize,
+}
+
+impl<'a> VectorStore for DiskVectors<'a> {
+    fn get_vector(&self, vector_id: &VectorID) -> Option<&[f32]> {
+        let index = vector_id.0 as usize;
+        if !self.slots.get(index)?.is_valid() {
+            return None;
+        }
+
+        let start = index * self.dimension;
+        self.values.get(start..start + self.dimension)
+    }
+}
+
+/// The graph layer nodes stored in the memory-mapped file.
+#[derive(Clone, Copy)]
+struct DiskLayer<'a> {
+    nodes: &'a [VectorID],
+    width: usize,
+}
+
+impl<'a> Layer for DiskLayer<'a> {
+    type Slice = &'a [VectorID];
+    fn nearest_iter(&self, vector_id: &VectorID) -> NearestIter<Self::Slice> {
+        let start = vector_id.0 as usize * self.width;
+        match self.nodes.get(start..start + self.width) {
+            Some(node) => NearestIter::new(node),
+            None => NearestIter::new(&[]),
+        }
+    }
+}
+
+// The casts below are sound because the memory map is page-aligned
+// and u32, f32, and VectorID share the same size and alignment.
+
+fn as_words(bytes: &[u8]) -> &[u32] {
+    let len = bytes.len() / 4;
+    unsafe { slice::from_raw_parts(bytes.as_ptr() as *const u32, len) }
+}
+
+fn as_floats(words: &[u32]) -> &[f32] {
+    unsafe { slice::from_raw_parts(words.as_ptr() as *const f32, words.len()) }
+}
+
+fn as_ids(words: &[u32]) -> &[VectorID] {
+    let ptr = words.as_ptr() as *const VectorID;
+    unsafe { slice::from_raw_parts(ptr, words.len()) }
+}
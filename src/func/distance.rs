@@ -0,0 +1,141 @@
+use std::sync::OnceLock;
+
+/// The function computing the squared Euclidean distance.
+type Kernel = fn(&[f32], &[f32]) -> f32;
+
+/// Returns the Euclidean distance between two vector values.
+pub fn euclidean(a: &[f32], b: &[f32]) -> f32 {
+    squared_euclidean(a, b).sqrt()
+}
+
+/// Returns the squared Euclidean distance between two vector values.
+/// It ranks the vectors the same as the Euclidean distance so the
+/// searches compare it and only take the square root of the results.
+///
+/// The kernel is selected on the first call using the instructions
+/// supported by the CPU with the scalar kernel as the fallback.
+pub fn squared_euclidean(a: &[f32], b: &[f32]) -> f32 {
+    assert_eq!(a.len(), b.len());
+    static KERNEL: OnceLock<Kernel> = OnceLock::new();
+    let kernel = KERNEL.get_or_init(select_kernel);
+    kernel(a, b)
+}
+
+/// Returns the fastest kernel supported by the CPU.
+fn select_kernel() -> Kernel {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx512f") {
+            return x86::squared_euclidean_avx512;
+        }
+
+        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+            return x86::squared_euclidean_avx2;
+        }
+
+        if is_x86_feature_detected!("sse") {
+            return x86::squared_euclidean_sse;
+        }
+    }
+
+    squared_euclidean_scalar
+}
+
+/// Returns the squared Euclidean distance without SIMD instructions.
+/// The separate sums let the compiler vectorize the loop when the
+/// target supports it.
+pub(crate) fn squared_euclidean_scalar(a: &[f32], b: &[f32]) -> f32 {
+    let mut sums = [0.0; 8];
+    let (a_chunks, b_chunks) = (a.chunks_exact(8), b.chunks_exact(8));
+    let (a_tail, b_tail) = (a_chunks.remainder(), b_chunks.remainder());
+
+    for (a, b) in a_chunks.zip(b_chunks) {
+        for i in 0..8 {
+            let difference = a[i] - b[i];
+            sums[i] += difference * difference;
+        }
+    }
+
+    let tail = a_tail.iter().zip(b_tail.iter());
+    let tail = tail.map(|(a, b)| (a - b) * (a - b)).sum::<f32>();
+    sums.iter().sum::<f32>() + tail
+}
+
+/// The kernels are only called after their CPU features are detected
+/// which makes the calls to the target feature functions safe.
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use super::squared_euclidean_scalar;
+    use std::arch::x86_64::*;
+
+    pub fn squared_euclidean_avx512(a: &[f32], b: &[f32]) -> f32 {
+        unsafe { avx512(a, b) }
+    }
+
+    pub fn squared_euclidean_avx2(a: &[f32], b: &[f32]) -> f32 {
+        unsafe { avx2(a, b) }
+    }
+
+    pub fn squared_euclidean_sse(a: &[f32], b: &[f32]) -> f32 {
+        unsafe { sse(a, b) }
+    }
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn avx512(a: &[f32], b: &[f32]) -> f32 {
+        let len = a.len() - a.len() % 16;
+        let mut sum = _mm512_setzero_ps();
+
+        for i in (0..len).step_by(16) {
+            let x = _mm512_loadu_ps(a.as_ptr().add(i));
+            let y = _mm512_loadu_ps(b.as_ptr().add(i));
+            let difference = _mm512_sub_ps(x, y);
+            sum = _mm512_fmadd_ps(difference, difference, sum);
+        }
+
+        let tail = squared_euclidean_scalar(&a[len..], &b[len..]);
+        _mm512_reduce_add_ps(sum) + tail
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn avx2(a: &[f32], b: &[f32]) -> f32 {
+        let len = a.len() - a.len() % 8;
+        let mut sum = _mm256_setzero_ps();
+
+        for i in (0..len).step_by(8) {
+            let x = _mm256_loadu_ps(a.as_ptr().add(i));
+            let y = _mm256_loadu_ps(b.as_ptr().add(i));
+            let difference = _mm256_sub_ps(x, y);
+            sum = _mm256_fmadd_ps(difference, difference, sum);
+        }
+
+        // Add the upper half to the lower half of the sum.
+        let high = _mm256_extractf128_ps(sum, 1);
+        let sum = _mm_add_ps(_mm256_castps256_ps128(sum), high);
+
+        let tail = squared_euclidean_scalar(&a[len..], &b[len..]);
+        horizontal_sum(sum) + tail
+    }
+
+    #[target_feature(enable = "sse")]
+    unsafe fn sse(a: &[f32], b: &[f32]) -> f32 {
+        let len = a.len() - a.len() % 4;
+        let mut sum = _mm_setzero_ps();
+
+        for i in (0..len).step_by(4) {
+            let x = _mm_loadu_ps(a.as_ptr().add(i));
+            let y = _mm_loadu_ps(b.as_ptr().add(i));
+            let difference = _mm_sub_ps(x, y);
+            sum = _mm_add_ps(sum, _mm_mul_ps(difference, difference));
+        }
+
+        let tail = squared_euclidean_scalar(&a[len..], &b[len..]);
+        horizontal_sum(sum) + tail
+    }
+
+    #[target_feature(enable = "sse")]
+    unsafe fn horizontal_sum(sum: __m128) -> f32 {
+        let mut lanes = [0.0; 4];
+        _mm_storeu_ps(lanes.as_mut_ptr(), sum);
+        lanes.iter().sum()
+    }
+}
@@ -1,3 +1,18 @@
 pub const COLLECTION_NOT_FOUND: &str = "The collection is not found.";
+pub const COLLECTION_EXISTS: &str = "The collection already exists.";
 pub const COLLECTION_LIMIT: &str = "The collection limit is reached.";
 pub const RECORD_NOT_FOUND: &str = "The record is not found.";
+pub const INVALID_METADATA: &str = "The metadata type is invalid.";
+pub const INDEX_NOT_FOUND: &str = "The metadata index is not found.";
+pub const INDEX_EXISTS: &str = "The metadata index already exists.";
+pub const TEXT_INDEX_NOT_FOUND: &str = "The text index is not configured.";
+pub const FIELD_NOT_FOUND: &str = "The vector field is not found.";
+pub const TARGET_RECALL_INVALID: &str =
+    "The target recall must be between 0 and 1.";
+pub const QUERIES_EMPTY: &str = "The queries to evaluate are empty.";
+pub const INDEX_TYPE_UNSUPPORTED: &str =
+    "The operation is not supported by the index type.";
+pub const FUSION_WEIGHT_INVALID: &str =
+    "The fusion weight must be between 0 and 1.";
+pub const WAL_IN_USE: &str =
+    "The write-ahead log is used by another collection handle.";
@@ -0,0 +1,326 @@
+use super::collection::*;
+use super::*;
+
+/// The method to combine the distances of the query vectors to the
+/// vectors of a record into the distance of the record.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aggregation {
+    /// The smallest distance between any of the query vectors and
+    /// any of the vectors of the record. This is the maximum
+    /// similarity of the best matching pair.
+    MaxSim,
+    /// The sum of the smallest distance of each query vector to the
+    /// vectors of the record in the same field. This is the late
+    /// interaction scoring used by ColBERT.
+    Sum,
+}
+
+/// The named vector field with its own HNSW index and dimension.
+/// A record can have multiple vectors in a field and each of them
+/// is a node of the field index.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct VectorField {
+    /// Index of the vectors in the field.
+    index: Collection,
+    /// Record IDs of the nodes in the field index.
+    records: HashMap<VectorID, VectorID>,
+    /// Node IDs of the records in the field index.
+    nodes: HashMap<VectorID, Vec<VectorID>>,
+}
+
+impl VectorField {
+    fn new(config: &Config) -> Self {
+        Self {
+            index: Collection::new(config),
+            records: HashMap::new(),
+            nodes: HashMap::new(),
+        }
+    }
+
+    /// Returns an error if the vectors can't be inserted to the field.
+    fn validate(&self, vectors: &[Vector]) -> Result<(), Box<dyn Error>> {
+        let dimension = match self.index.dimension() {
+            0 => vectors.first().map_or(0, |vector| vector.len()),
+            dimension => dimension,
+        };
+
+        for vector in vectors.iter() {
+            vector.validate()?;
+            if vector.len() != dimension {
+                let message = format!(
+                    "Invalid vector field dimension. Expected dimension of {}.",
+                    dimension
+                );
+
+                return Err(message.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn insert(
+        &mut self,
+        id: VectorID,
+        vectors: &[Vector],
+    ) -> Result<(), Box<dyn Error>> {
+        let mut nodes = Vec::with_capacity(vectors.len());
+        for vector in vectors.iter() {
+            let node: VectorID = self.index.slots.len().into();
+            self.index.insert(&Record::new(vector, &Metadata::Null))?;
+            self.records.insert(node, id);
+            nodes.push(node);
+        }
+
+        if !nodes.is_empty() {
+            self.nodes.insert(id, nodes);
+        }
+
+        Ok(())
+    }
+
+    fn remove(&mut self, id: &VectorID) -> Result<(), Box<dyn Error>> {
+        for node in self.nodes.remove(id).unwrap_or_default() {
+            self.index.delete(&node)?;
+            self.records.remove(&node);
+        }
+
+        Ok(())
+    }
+
+    fn get(&self, id: &VectorID) -> Option<Vec<Vector>> {
+        let nodes = self.nodes.get(id)?;
+        let vectors =
+            nodes.iter().filter_map(|node| self.index.get_vector(node));
+        Some(vectors.map(Vector::from).collect())
+    }
+
+    /// Returns the IDs of the records with the nearest vectors.
+    fn search_records(
+        &self,
+        vector: &Vector,
+    ) -> Result<Vec<VectorID>, Box<dyn Error>> {
+        let ef = self.index.config.ef_search;
+        let nearest = self.index.search_ids(vector, ef)?;
+        Ok(nearest.iter().map(|(node, _)| self.records[node]).collect())
+    }
+
+    /// Returns the smallest distance of the vector to the record.
+    fn min_distance(&self, id: &VectorID, vector: &Vector) -> Option<f32> {
+        let nodes = self.nodes.get(id)?;
+        let distances = nodes.iter().filter_map(|node| {
+            let values = self.index.get_vector(node)?;
+            Some(euclidean(&vector.0, values))
+        });
+        distances.min_by(|a, b| a.total_cmp(b))
+    }
+}
+
+impl Clone for VectorField {
+    fn clone(&self) -> Self {
+        // The field index never has a log or other indexes attached.
+        let index = Collection {
+            config: self.index.config.clone(),
+            data: self.index.data.clone(),
+            vectors: self.index.vectors.clone(),
+            slots: self.index.slots.clone(),
+            base_layer: self.index.base_layer.clone(),
+            upper_layers: self.index.upper_layers.clone(),
+            count: self.index.count,
+            dimension: self.index.dimension,
+            ..Collection::new(&self.index.config)
+        };
+
+        Self { index, records: self.records.clone(), nodes: self.nodes.clone() }
+    }
+}
+
+/// The named vector fields of the collection by their name.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub(crate) struct VectorFields(HashMap<String, VectorField>);
+
+impl VectorFields {
+    /// Creates the empty fields declared in the configuration.
+    pub fn new(config: &Config) -> Self {
+        let field_config = Self::field_config(config);
+        let iter = config.vector_fields.iter();
+        let new_field =
+            |name: &String| (name.clone(), VectorField::new(&field_config));
+
+        Self(iter.map(new_field).collect())
+    }
+
+    /// Builds the field indexes from the vectors of the records.
+    /// * `config`: Collection configuration.
+    /// * `records`: Records in the order of their IDs.
+    pub fn build(
+        config: &Config,
+        records: &[Record],
+    ) -> Result<Self, Box<dyn Error>> {
+        // Ensure that the records only have the declared fields.
+        let names = &config.vector_fields;
+        for record in records.iter() {
+            if record.vectors.keys().any(|name| !names.contains(name)) {
+                return Err(err::FIELD_NOT_FOUND.into());
+            }
+        }
+
+        let field_config = Self::field_config(config);
+        let mut fields = HashMap::new();
+
+        for name in config.vector_fields.iter() {
+            let mut field = VectorField::new(&field_config);
+            let mut nodes = vec![];
+
+            for (i, record) in records.iter().enumerate() {
+                let vectors = match record.vectors.get(name) {
+                    Some(vectors) if !vectors.is_empty() => vectors,
+                    _ => continue,
+                };
+
+                let id: VectorID = i.into();
+                let start = nodes.len();
+                for vector in vectors.iter() {
+                    let node: VectorID = nodes.len().into();
+                    field.records.insert(node, id);
+                    nodes.push(Record::new(vector, &Metadata::Null));
+                }
+
+                let ids = (start..nodes.len()).map(VectorID::from).collect();
+                field.nodes.insert(id, ids);
+            }
+
+            field.index = Collection::build(&field_config, &nodes)?;
+            fields.insert(name.clone(), field);
+        }
+
+        Ok(Self(fields))
+    }
+
+    /// Returns the configuration of the field indexes.
+    /// Only the index options and the normalization are inherited.
+    /// The fields use HNSW instead of IVF since the IVF centroids are
+    /// trained on the vectors of the main index.
+    pub fn field_config(config: &Config) -> Config {
+        Config {
+            ef_construction: config.ef_construction,
+            ef_search: config.ef_search,
+            ml: config.ml,
+            normalize: config.normalize,
+            index: match config.index {
+                IndexType::Ivf { .. } => IndexType::Hnsw,
+                index => index,
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Applies the configuration after the fields are deserialized
+    /// since the stored field indexes only keep the index options.
+    pub fn configure(&mut self, config: &Config) {
+        let config = Self::field_config(config);
+        for field in self.0.values_mut() {
+            field.index.config = config.clone();
+        }
+    }
+
+    /// Returns an error if the vectors of a record are for unknown
+    /// fields or can't be inserted to their fields.
+    pub fn validate(
+        &self,
+        vectors: &HashMap<String, Vec<Vector>>,
+    ) -> Result<(), Box<dyn Error>> {
+        for (name, vectors) in vectors.iter() {
+            self.field(name)?.validate(vectors)?;
+        }
+
+        Ok(())
+    }
+
+    /// Inserts the vectors of a record to their fields.
+    pub fn insert(
+        &mut self,
+        id: VectorID,
+        vectors: &HashMap<String, Vec<Vector>>,
+    ) -> Result<(), Box<dyn Error>> {
+        for (name, vectors) in vectors.iter() {
+            match self.0.get_mut(name) {
+                Some(field) => field.insert(id, vectors)?,
+                None => return Err(err::FIELD_NOT_FOUND.into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes the vectors of a record from all fields.
+    pub fn remove(&mut self, id: &VectorID) -> Result<(), Box<dyn Error>> {
+        for field in self.0.values_mut() {
+            field.remove(id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the vectors of a record by their field name.
+    pub fn get(&self, id: &VectorID) -> HashMap<String, Vec<Vector>> {
+        let iter = self.0.iter();
+        let fields =
+            iter.filter_map(|(name, field)| Some((name, field.get(id)?)));
+        fields.map(|(name, vectors)| (name.clone(), vectors)).collect()
+    }
+
+    /// Returns the records nearest to the query vectors of the fields.
+    /// The candidates found in the field indexes are ranked by their
+    /// exact aggregated distance. Records without vectors in all of
+    /// the queried fields are excluded.
+    pub fn search(
+        &self,
+        queries: &HashMap<String, Vec<Vector>>,
+        n: usize,
+        aggregation: Aggregation,
+    ) -> Result<Vec<(VectorID, f32)>, Box<dyn Error>> {
+        let mut candidates = vec![];
+        for (name, vectors) in queries.iter() {
+            let field = self.field(name)?;
+            for vector in vectors.iter() {
+                candidates.extend(field.search_records(vector)?);
+            }
+        }
+
+        candidates.sort();
+        candidates.dedup();
+
+        let distance = |id: &VectorID| {
+            let mut distances = vec![];
+            for (name, vectors) in queries.iter() {
+                for vector in vectors.iter() {
+                    distances.push(self.0[name].min_distance(id, vector)?);
+                }
+            }
+
+            match aggregation {
+                Aggregation::MaxSim => {
+                    distances.into_iter().min_by(|a, b| a.total_cmp(b))
+                }
+                Aggregation::Sum => Some(distances.into_iter().sum()),
+            }
+        };
+
+        let mut nearest: Vec<(VectorID, f32)> = candidates
+            .into_iter()
+            .filter_map(|id| Some((id, distance(&id)?)))
+            .collect();
+
+        nearest.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        nearest.truncate(n);
+        Ok(nearest)
+    }
+
+    fn field(&self, name: &str) -> Result<&VectorField, Box<dyn Error>> {
+        match self.0.get(name) {
+            Some(field) => Ok(field),
+            None => Err(err::FIELD_NOT_FOUND.into()),
+        }
+    }
+}
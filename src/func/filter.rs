@@ -0,0 +1,359 @@
+use super::*;
+use std::collections::{BTreeMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::ops::{Bound, RangeBounds};
+
+/// The data structure of a metadata index.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndexKind {
+    /// Hash index for exact lookups of text, numbers, and booleans.
+    Hash,
+    /// B-tree index for exact lookups and range queries.
+    BTree,
+}
+
+/// The filter to find vector records by their metadata.
+/// The metadata paths used in the filter must be indexed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Filter {
+    /// The value at the path equals the metadata. Arrays match
+    /// if any of their items is equal to the metadata.
+    Equal(String, Metadata),
+    /// The value at the path is within the lower and upper bounds.
+    /// This requires the path to have a B-tree index.
+    Range(String, Bound<Metadata>, Bound<Metadata>),
+    /// All of the filters match. Matches nothing if empty.
+    And(Vec<Filter>),
+    /// Any of the filters match. Matches nothing if empty.
+    Or(Vec<Filter>),
+}
+
+impl Filter {
+    /// Creates a filter matching the value at the path.
+    /// * `path`: Metadata path like `author.name`.
+    /// * `value`: Value to match.
+    pub fn equal(path: &str, value: impl Into<Metadata>) -> Self {
+        Filter::Equal(path.to_string(), value.into())
+    }
+
+    /// Creates a filter matching the values at the path in the range.
+    /// * `path`: Metadata path like `published.year`.
+    /// * `range`: Range of the values like `2000..2010`.
+    pub fn range<T: Into<Metadata> + Clone>(
+        path: &str,
+        range: impl RangeBounds<T>,
+    ) -> Self {
+        let start = range.start_bound().cloned().map(Into::into);
+        let end = range.end_bound().cloned().map(Into::into);
+        Filter::Range(path.to_string(), start, end)
+    }
+
+    /// Returns the IDs of the records matching the filter.
+    /// * `indexes`: Metadata indexes by their path.
+    pub(crate) fn find_ids(
+        &self,
+        indexes: &HashMap<String, MetadataIndex>,
+    ) -> Result<HashSet<VectorID>, Box<dyn Error>> {
+        let index = |path: &str| match indexes.get(path) {
+            Some(index) => Ok(index),
+            None => Err(Box::<dyn Error>::from(err::INDEX_NOT_FOUND)),
+        };
+
+        match self {
+            Filter::Equal(path, value) => Ok(index(path)?.equal(value)),
+            Filter::Range(path, start, end) => {
+                index(path)?.range(start.as_ref(), end.as_ref())
+            }
+            Filter::And(filters) => {
+                let mut iter = filters.iter();
+                let mut ids = match iter.next() {
+                    Some(filter) => filter.find_ids(indexes)?,
+                    None => return Ok(HashSet::new()),
+                };
+
+                for filter in iter {
+                    let other = filter.find_ids(indexes)?;
+                    ids.retain(|id| other.contains(id));
+                }
+
+                Ok(ids)
+            }
+            Filter::Or(filters) => {
+                let mut ids = HashSet::new();
+                for filter in filters.iter() {
+                    ids.extend(filter.find_ids(indexes)?);
+                }
+
+                Ok(ids)
+            }
+        }
+    }
+}
+
+/// The index mapping the metadata values at a path to vector IDs.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) enum MetadataIndex {
+    Hash(HashMap<IndexKey, HashSet<VectorID>>),
+    BTree(BTreeMap<IndexKey, HashSet<VectorID>>),
+}
+
+impl MetadataIndex {
+    /// Creates an empty index of the given kind.
+    pub fn new(kind: IndexKind) -> Self {
+        match kind {
+            IndexKind::Hash => MetadataIndex::Hash(HashMap::new()),
+            IndexKind::BTree => MetadataIndex::BTree(BTreeMap::new()),
+        }
+    }
+
+    /// Returns the data structure of the index.
+    pub fn kind(&self) -> IndexKind {
+        match self {
+            MetadataIndex::Hash(_) => IndexKind::Hash,
+            MetadataIndex::BTree(_) => IndexKind::BTree,
+        }
+    }
+
+    /// Adds the vector ID under the keys of the metadata value.
+    pub fn insert(&mut self, id: VectorID, value: &Metadata) {
+        for key in IndexKey::from_metadata(value) {
+            let ids = match self {
+                MetadataIndex::Hash(map) => map.entry(key).or_default(),
+                MetadataIndex::BTree(map) => map.entry(key).or_default(),
+            };
+
+            ids.insert(id);
+        }
+    }
+
+    /// Removes the vector ID from the keys of the metadata value.
+    pub fn remove(&mut self, id: &VectorID, value: &Metadata) {
+        for key in IndexKey::from_metadata(value) {
+            // Remove the key once no vector has the value.
+            let is_empty = match self {
+                MetadataIndex::Hash(map) => remove_id(map.get_mut(&key), id),
+                MetadataIndex::BTree(map) => remove_id(map.get_mut(&key), id),
+            };
+
+            if is_empty {
+                match self {
+                    MetadataIndex::Hash(map) => map.remove(&key),
+                    MetadataIndex::BTree(map) => map.remove(&key),
+                };
+            }
+        }
+    }
+
+    /// Returns the IDs of the vectors with the value.
+    pub fn equal(&self, value: &Metadata) -> HashSet<VectorID> {
+        let mut ids = HashSet::new();
+        for key in IndexKey::from_metadata(value) {
+            let matches = match self {
+                MetadataIndex::Hash(map) => map.get(&key),
+                MetadataIndex::BTree(map) => map.get(&key),
+            };
+
+            ids.extend(matches.into_iter().flatten());
+        }
+
+        ids
+    }
+
+    /// Returns the IDs of the vectors with values within the bounds.
+    pub fn range(
+        &self,
+        start: Bound<&Metadata>,
+        end: Bound<&Metadata>,
+    ) -> Result<HashSet<VectorID>, Box<dyn Error>> {
+        let map = match self {
+            MetadataIndex::BTree(map) => map,
+            MetadataIndex::Hash(_) => {
+                let message = "The hash index doesn't support range queries.";
+                return Err(message.into());
+            }
+        };
+
+        let bound = |bound: Bound<&Metadata>| match bound {
+            Bound::Included(value) => {
+                IndexKey::from_bound(value).map(Bound::Included)
+            }
+            Bound::Excluded(value) => {
+                IndexKey::from_bound(value).map(Bound::Excluded)
+            }
+            Bound::Unbounded => Ok(Bound::Unbounded),
+        };
+
+        let (start, end) = (bound(start)?, bound(end)?);
+
+        // Unbounded ranges only match the values of the same type.
+        let kind = match (&start, &end) {
+            (Bound::Included(key) | Bound::Excluded(key), _) => Some(key),
+            (_, Bound::Included(key) | Bound::Excluded(key)) => Some(key),
+            _ => None,
+        };
+
+        // BTreeMap::range panics if the start is after the end.
+        if let Bound::Included(a) | Bound::Excluded(a) = &start {
+            if let Bound::Included(b) | Bound::Excluded(b) = &end {
+                let is_empty = match (&start, &end) {
+                    (Bound::Included(_), Bound::Included(_)) => a > b,
+                    _ => a >= b,
+                };
+
+                if is_empty {
+                    return Ok(HashSet::new());
+                }
+            }
+        }
+
+        let mut ids = HashSet::new();
+        for (key, matches) in map.range((start.as_ref(), end.as_ref())) {
+            if kind.is_none_or(|kind| key.same_type(kind)) {
+                ids.extend(matches.iter());
+            }
+        }
+
+        Ok(ids)
+    }
+}
+
+/// Removes the ID from the set and returns true if it's empty.
+fn remove_id(ids: Option<&mut HashSet<VectorID>>, id: &VectorID) -> bool {
+    match ids {
+        Some(ids) => {
+            ids.remove(id);
+            ids.is_empty()
+        }
+        None => false,
+    }
+}
+
+/// The key of a metadata value in the index. The numbers of all
+/// types share the same key so that, for example, `Integer(1)` and
+/// `Signed(1)` are equal.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum IndexKey {
+    Null,
+    Boolean(bool),
+    Number(Number),
+    Text(String),
+    Timestamp(i64),
+}
+
+impl IndexKey {
+    /// Returns the keys of a metadata value. Arrays have a key for
+    /// each item while objects, bytes, and NaN are not indexed.
+    fn from_metadata(value: &Metadata) -> Vec<IndexKey> {
+        match value {
+            Metadata::Array(items) => {
+                items.iter().filter_map(Self::from_scalar).collect()
+            }
+            _ => Self::from_scalar(value).into_iter().collect(),
+        }
+    }
+
+    fn from_scalar(value: &Metadata) -> Option<IndexKey> {
+        let key = match value {
+            Metadata::Null => IndexKey::Null,
+            Metadata::Boolean(value) => IndexKey::Boolean(*value),
+            Metadata::Text(value) => IndexKey::Text(value.clone()),
+            Metadata::Timestamp(value) => IndexKey::Timestamp(*value),
+            Metadata::Integer(value) => Number::Int(*value as i128).into(),
+            Metadata::Signed(value) => Number::Int(*value as i128).into(),
+            Metadata::Float(value) => Number::from_f64(*value as f64)?.into(),
+            Metadata::Double(value) => Number::from_f64(*value)?.into(),
+            _ => return None,
+        };
+
+        Some(key)
+    }
+
+    /// Returns the key of a range bound.
+    fn from_bound(value: &Metadata) -> Result<IndexKey, Box<dyn Error>> {
+        match Self::from_scalar(value) {
+            Some(key) => Ok(key),
+            None => Err("The range bound must be a single value.".into()),
+        }
+    }
+
+    fn same_type(&self, other: &IndexKey) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+impl From<Number> for IndexKey {
+    fn from(number: Number) -> Self {
+        IndexKey::Number(number)
+    }
+}
+
+/// A number with a total order across integers and floats.
+/// Integral floats are stored as integers to keep the keys unique.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub(crate) enum Number {
+    Int(i128),
+    // Never NaN or integral.
+    Float(f64),
+}
+
+impl Number {
+    fn from_f64(value: f64) -> Option<Self> {
+        if value.is_nan() {
+            return None;
+        }
+
+        let in_range = value.abs() < i128::MAX as f64;
+        if value.fract() == 0.0 && in_range {
+            Some(Number::Int(value as i128))
+        } else {
+            Some(Number::Float(value))
+        }
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Number {}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Number {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => a.cmp(b),
+            (Number::Float(a), Number::Float(b)) => a.total_cmp(b),
+            (Number::Int(a), Number::Float(b)) => cmp_int_float(*a, *b),
+            (Number::Float(a), Number::Int(b)) => {
+                cmp_int_float(*b, *a).reverse()
+            }
+        }
+    }
+}
+
+impl Hash for Number {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Number::Int(value) => (0, *value).hash(state),
+            Number::Float(value) => (1, value.to_bits()).hash(state),
+        }
+    }
+}
+
+/// Compares an integer with a float that is infinite or not integral.
+/// Such floats are smaller than 2^53 where the integer conversion is
+/// exact, so the comparison is exact and never equal.
+fn cmp_int_float(int: i128, float: f64) -> Ordering {
+    match (int as f64).partial_cmp(&float) {
+        Some(Ordering::Equal) | None => unreachable!(),
+        Some(ordering) => ordering,
+    }
+}
@@ -0,0 +1,51 @@
+use super::*;
+
+/// Returns the exact nearest neighbors scanning all of the vectors.
+/// The vectors are read in the slot order of the arena.
+/// * `vectors`: Vectors of the collection.
+/// * `vector`: Vector to search.
+/// * `n`: Number of neighbors to return.
+pub(crate) fn search(
+    vectors: &VectorArena,
+    vector: &Vector,
+    n: usize,
+) -> Vec<(VectorID, f32)> {
+    if vectors.is_empty() || n == 0 {
+        return vec![];
+    }
+
+    let distances = vectors.par_iter();
+    let distances =
+        distances.map(|(id, other)| (id, squared_euclidean(&vector.0, other)));
+    select_nearest(distances, n)
+}
+
+/// Returns the `n` smallest distances sorted by the distance and then
+/// by the ID. Each thread only keeps its `n` nearest in a heap.
+/// The squared distances are compared and the Euclidean distances
+/// are returned.
+pub(crate) fn select_nearest(
+    distances: impl ParallelIterator<Item = (VectorID, f32)>,
+    n: usize,
+) -> Vec<(VectorID, f32)> {
+    let push = |mut heap: BinaryHeap<(OrderedFloat<f32>, VectorID)>,
+                (id, distance): (VectorID, f32)| {
+        heap.push((OrderedFloat(distance), id));
+        if heap.len() > n {
+            heap.pop();
+        }
+
+        heap
+    };
+
+    let heap = distances.fold(BinaryHeap::new, push).reduce(
+        BinaryHeap::new,
+        |heap, other| {
+            let iter = other.into_iter().map(|(d, id)| (id, d.0));
+            iter.fold(heap, push)
+        },
+    );
+
+    let iter = heap.into_sorted_vec().into_iter();
+    iter.map(|(distance, id)| (id, distance.sqrt())).collect()
+}
@@ -0,0 +1,192 @@
+use super::*;
+
+/// Number of k-means iterations to train the centroids.
+const ITERATIONS: usize = 10;
+/// Maximum number of training vectors for each list.
+const SAMPLES_PER_LIST: usize = 256;
+
+/// The inverted file index partitioning the vectors into lists by
+/// their nearest centroid. The search only scans the lists of the
+/// centroids nearest to the query.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub(crate) struct IvfIndex {
+    /// Target number of lists.
+    lists: usize,
+    centroids: Vec<Vector>,
+    postings: Vec<Vec<VectorID>>,
+    /// Lists of the vectors by their ID.
+    assignments: HashMap<VectorID, usize>,
+    /// Number of vectors when the centroids were trained.
+    trained: usize,
+}
+
+impl IvfIndex {
+    /// Trains the centroids with k-means and assigns the vectors.
+    /// * `lists`: Number of lists to partition the vectors into.
+    /// * `vectors`: Vectors of the collection.
+    pub fn build(lists: usize, vectors: &VectorArena) -> Self {
+        let mut index = Self {
+            lists: lists.max(1),
+            trained: vectors.len(),
+            ..Default::default()
+        };
+
+        let k = index.lists.min(vectors.len());
+        if k == 0 {
+            return index;
+        }
+
+        // Train on evenly spaced samples of the vectors. The arena is
+        // in the slot order so the same vectors train the same lists.
+        let step = (vectors.len() / (k * SAMPLES_PER_LIST)).max(1);
+        let iter = vectors.iter().step_by(step);
+        let samples: Vec<&[f32]> = iter.map(|(_, vector)| vector).collect();
+
+        let step = samples.len() / k;
+        let iter = samples.iter().step_by(step).take(k);
+        index.centroids = iter.map(|vector| vector.to_vec().into()).collect();
+        for _ in 0..ITERATIONS {
+            index.train(&samples);
+        }
+
+        index.postings = vec![vec![]; k];
+        for (id, vector) in vectors.iter() {
+            let list = index.nearest_list(vector);
+            index.postings[list].push(id);
+            index.assignments.insert(id, list);
+        }
+
+        index
+    }
+
+    /// Moves each centroid to the mean of its nearest samples.
+    /// Centroids without any sample are kept as they are.
+    fn train(&mut self, samples: &[&[f32]]) {
+        let dimension = self.centroids[0].len();
+        let empty = || vec![(vec![0.0; dimension], 0); self.centroids.len()];
+
+        let sums = samples
+            .par_iter()
+            .fold(empty, |mut sums, vector| {
+                let (sum, count) = &mut sums[self.nearest_list(vector)];
+                for (sum, value) in sum.iter_mut().zip(vector.iter()) {
+                    *sum += value;
+                }
+
+                *count += 1;
+                sums
+            })
+            .reduce(empty, |mut sums, other| {
+                for ((sum, count), (other, n)) in sums.iter_mut().zip(other) {
+                    for (sum, value) in sum.iter_mut().zip(other) {
+                        *sum += value;
+                    }
+
+                    *count += n;
+                }
+
+                sums
+            });
+
+        for (centroid, (sum, count)) in self.centroids.iter_mut().zip(sums) {
+            if count > 0 {
+                let mean = sum.into_iter().map(|value| value / count as f32);
+                *centroid = mean.collect::<Vec<f32>>().into();
+            }
+        }
+    }
+
+    /// Returns true once the index doubles in size since its
+    /// centroids were trained. The first vectors inserted into an
+    /// empty index are only placeholders for the centroids.
+    pub fn should_retrain(&self) -> bool {
+        self.assignments.len() >= self.trained.max(self.lists) * 2
+    }
+
+    /// Trains the centroids again and reassigns the vectors.
+    /// * `vectors`: Vectors of the collection.
+    pub fn retrain(&mut self, vectors: &VectorArena) {
+        *self = Self::build(self.lists, vectors);
+    }
+
+    /// Adds or moves the vector of a record to its nearest list.
+    /// The first vectors inserted into an empty index become the
+    /// centroids until the target number of lists is reached.
+    pub fn insert(&mut self, id: VectorID, vector: &[f32]) {
+        self.remove(&id);
+
+        let list = match self.centroids.len() < self.lists {
+            true => {
+                self.centroids.push(vector.to_vec().into());
+                self.postings.push(vec![]);
+                self.centroids.len() - 1
+            }
+            false => self.nearest_list(vector),
+        };
+
+        self.postings[list].push(id);
+        self.assignments.insert(id, list);
+    }
+
+    /// Removes the vector of a record if it's in the index.
+    pub fn remove(&mut self, id: &VectorID) {
+        if let Some(list) = self.assignments.remove(id) {
+            let postings = &mut self.postings[list];
+            if let Some(position) = postings.iter().position(|i| i == id) {
+                postings.swap_remove(position);
+            }
+        }
+    }
+
+    /// Returns the nearest neighbors from the lists of the nearest
+    /// centroids sorted by the distance.
+    /// * `vectors`: Vectors of the collection.
+    /// * `n`: Number of neighbors to return.
+    /// * `probes`: Number of lists to scan.
+    pub fn search(
+        &self,
+        vector: &Vector,
+        vectors: &VectorArena,
+        n: usize,
+        probes: usize,
+    ) -> Vec<(VectorID, f32)> {
+        self.search_counted(vector, vectors, n, probes).0
+    }
+
+    /// Searches like `search` and also returns the number of the
+    /// distances computed to the centroids and the listed vectors.
+    pub fn search_counted(
+        &self,
+        vector: &Vector,
+        vectors: &VectorArena,
+        n: usize,
+        probes: usize,
+    ) -> (Vec<(VectorID, f32)>, usize) {
+        let centroids = self.centroids.iter().enumerate();
+        let distances =
+            centroids.map(|(i, c)| (i, squared_euclidean(&vector.0, &c.0)));
+        let mut lists = Vec::from_iter(distances);
+        lists.sort_by(|a, b| a.1.total_cmp(&b.1));
+        lists.truncate(probes.max(1));
+
+        let listed = lists.iter().map(|(list, _)| self.postings[*list].len());
+        let count = self.centroids.len() + listed.sum::<usize>();
+
+        let ids = lists
+            .par_iter()
+            .flat_map_iter(|(list, _)| self.postings[*list].iter());
+
+        let distances =
+            ids.map(|id| (*id, squared_euclidean(&vector.0, &vectors[id])));
+        (select_nearest(distances, n), count)
+    }
+
+    /// Returns the list of the centroid nearest to the vector.
+    fn nearest_list(&self, vector: &[f32]) -> usize {
+        let centroids = self.centroids.iter();
+        let iter = centroids.map(|c| squared_euclidean(vector, &c.0));
+        let distances = iter.enumerate();
+        let nearest = distances.min_by(|a, b| a.1.total_cmp(&b.1));
+        nearest.map_or(0, |(list, _)| list)
+    }
+}
@@ -1,6 +1,14 @@
+use super::convert::*;
 use super::*;
+use serde::de::DeserializeOwned;
+use serde_json::{Number, Value};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// The metadata associated with a vector record.
+///
+/// New variants are only appended at the end of the enum because the
+/// stored collections encode the variants by their index. This allows
+/// the collections stored by the older versions to load as is.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum Metadata {
     /// A piece of text like article title or description.
@@ -13,6 +21,173 @@ pub enum Metadata {
     Array(Vec<Metadata>),
     /// A map of string and metadata pairs. The most common type.
     Object(HashMap<String, Metadata>),
+    /// A boolean value like a flag or a toggle.
+    Boolean(bool),
+    /// A missing or an unknown value.
+    Null,
+    /// A signed integer number that can be negative.
+    Signed(i64),
+    /// A double precision float number.
+    Double(f64),
+    /// Raw bytes such as an image thumbnail.
+    Bytes(Vec<u8>),
+    /// A point in time in milliseconds since the UNIX epoch.
+    Timestamp(i64),
+}
+
+pub use super::convert::{ConvertError, MetadataSerializer};
+
+impl Metadata {
+    /// Converts a serializable value like a struct to the metadata.
+    /// Structs and maps become objects and sequences become arrays.
+    /// * `value`: Value to convert.
+    pub fn from_serializable<T: Serialize + ?Sized>(
+        value: &T,
+    ) -> Result<Self, Box<dyn Error>> {
+        to_metadata(value)
+    }
+
+    /// Converts the metadata to a deserializable type like a struct.
+    pub fn deserialize_into<T: DeserializeOwned>(
+        self,
+    ) -> Result<T, Box<dyn Error>> {
+        from_metadata(self)
+    }
+
+    /// Returns the value nested in objects and arrays by its path.
+    /// The keys and the array indexes are separated by dots.
+    /// * `path`: Path of the value like `author.names.0`.
+    pub fn get(&self, path: &str) -> Option<&Metadata> {
+        let mut current = self;
+        for key in path.split('.') {
+            current = match current {
+                Metadata::Object(obj) => obj.get(key)?,
+                Metadata::Array(arr) => arr.get(key.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+
+        Some(current)
+    }
+
+    /// Merges the patch into the metadata following JSON merge patch.
+    /// Object patches update the keys recursively and remove the keys
+    /// set to null. Any other patch replaces the metadata.
+    /// * `patch`: Changes to merge.
+    pub fn merge(&mut self, patch: &Metadata) {
+        let patch = match patch {
+            Metadata::Object(patch) => patch,
+            _ => return *self = patch.clone(),
+        };
+
+        if !matches!(self, Metadata::Object(_)) {
+            *self = Metadata::Object(HashMap::new());
+        }
+
+        let obj = match self {
+            Metadata::Object(obj) => obj,
+            _ => unreachable!(),
+        };
+
+        for (key, value) in patch.iter() {
+            if value.is_null() {
+                obj.remove(key);
+            } else {
+                obj.entry(key.clone()).or_insert(Metadata::Null).merge(value);
+            }
+        }
+    }
+
+    /// Returns true if the metadata is null.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Metadata::Null)
+    }
+
+    /// Returns the boolean value if the metadata is a boolean.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Metadata::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the text if the metadata is a text.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Metadata::Text(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of integer or signed integer metadata
+    /// if it fits in a signed 64-bit integer.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Metadata::Integer(value) => i64::try_from(*value).ok(),
+            Metadata::Signed(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of integer or signed integer metadata
+    /// if it's not negative.
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            Metadata::Integer(value) => Some(*value),
+            Metadata::Signed(value) => usize::try_from(*value).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of any numeric metadata as a double.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Metadata::Integer(value) => Some(*value as f64),
+            Metadata::Float(value) => Some(*value as f64),
+            Metadata::Signed(value) => Some(*value as f64),
+            Metadata::Double(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the bytes if the metadata is raw bytes.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Metadata::Bytes(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the point in time if the metadata is a timestamp.
+    pub fn as_timestamp(&self) -> Option<SystemTime> {
+        let millis = match self {
+            Metadata::Timestamp(value) => *value,
+            _ => return None,
+        };
+
+        let duration = Duration::from_millis(millis.unsigned_abs());
+        if millis < 0 {
+            UNIX_EPOCH.checked_sub(duration)
+        } else {
+            UNIX_EPOCH.checked_add(duration)
+        }
+    }
+
+    /// Returns the items if the metadata is an array.
+    pub fn as_array(&self) -> Option<&Vec<Metadata>> {
+        match self {
+            Metadata::Array(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the key-value pairs if the metadata is an object.
+    pub fn as_object(&self) -> Option<&HashMap<String, Metadata>> {
+        match self {
+            Metadata::Object(value) => Some(value),
+            _ => None,
+        }
+    }
 }
 
 impl From<usize> for Metadata {
@@ -21,12 +196,65 @@ impl From<usize> for Metadata {
     }
 }
 
+impl From<i32> for Metadata {
+    fn from(value: i32) -> Self {
+        Metadata::Signed(value as i64)
+    }
+}
+
+impl From<i64> for Metadata {
+    fn from(value: i64) -> Self {
+        Metadata::Signed(value)
+    }
+}
+
 impl From<f32> for Metadata {
     fn from(value: f32) -> Self {
         Metadata::Float(value)
     }
 }
 
+impl From<f64> for Metadata {
+    fn from(value: f64) -> Self {
+        Metadata::Double(value)
+    }
+}
+
+impl From<bool> for Metadata {
+    fn from(value: bool) -> Self {
+        Metadata::Boolean(value)
+    }
+}
+
+impl From<&[u8]> for Metadata {
+    fn from(value: &[u8]) -> Self {
+        Metadata::Bytes(value.to_vec())
+    }
+}
+
+impl From<SystemTime> for Metadata {
+    fn from(value: SystemTime) -> Self {
+        let millis = match value.duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_millis() as i64,
+            Err(error) => -(error.duration().as_millis() as i64),
+        };
+
+        Metadata::Timestamp(millis)
+    }
+}
+
+impl<T> From<Option<T>> for Metadata
+where
+    Metadata: From<T>,
+{
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => value.into(),
+            None => Metadata::Null,
+        }
+    }
+}
+
 impl From<String> for Metadata {
     fn from(value: String) -> Self {
         Metadata::Text(value)
@@ -70,3 +298,115 @@ where
         Metadata::Object(obj)
     }
 }
+
+// Conversions from the metadata back to the primitive types.
+// The conversion fails if the metadata type doesn't match.
+
+macro_rules! impl_try_from {
+    ($type:ty, $method:ident) => {
+        impl TryFrom<Metadata> for $type {
+            type Error = Box<dyn Error>;
+            fn try_from(value: Metadata) -> Result<Self, Self::Error> {
+                value.$method().ok_or_else(|| err::INVALID_METADATA.into())
+            }
+        }
+    };
+}
+
+impl_try_from!(bool, as_bool);
+impl_try_from!(i64, as_i64);
+impl_try_from!(usize, as_usize);
+impl_try_from!(f64, as_f64);
+impl_try_from!(SystemTime, as_timestamp);
+
+impl TryFrom<Metadata> for f32 {
+    type Error = Box<dyn Error>;
+    fn try_from(value: Metadata) -> Result<Self, Self::Error> {
+        match value {
+            Metadata::Float(value) => Ok(value),
+            _ => Err(err::INVALID_METADATA.into()),
+        }
+    }
+}
+
+impl TryFrom<Metadata> for String {
+    type Error = Box<dyn Error>;
+    fn try_from(value: Metadata) -> Result<Self, Self::Error> {
+        match value {
+            Metadata::Text(value) => Ok(value),
+            _ => Err(err::INVALID_METADATA.into()),
+        }
+    }
+}
+
+impl TryFrom<Metadata> for Vec<u8> {
+    type Error = Box<dyn Error>;
+    fn try_from(value: Metadata) -> Result<Self, Self::Error> {
+        match value {
+            Metadata::Bytes(value) => Ok(value),
+            _ => Err(err::INVALID_METADATA.into()),
+        }
+    }
+}
+
+// Conversions between the metadata and the JSON values. The JSON values
+// convert to the metadata without loss. The bytes become an array of
+// numbers and the timestamps become the number of milliseconds.
+
+impl From<Value> for Metadata {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => Metadata::Null,
+            Value::Bool(value) => Metadata::Boolean(value),
+            Value::String(value) => Metadata::Text(value),
+            Value::Number(number) => {
+                if let Some(value) = number.as_u64() {
+                    match usize::try_from(value) {
+                        Ok(value) => Metadata::Integer(value),
+                        Err(_) => Metadata::Double(value as f64),
+                    }
+                } else if let Some(value) = number.as_i64() {
+                    Metadata::Signed(value)
+                } else {
+                    Metadata::Double(number.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+            Value::Array(arr) => {
+                Metadata::Array(arr.into_iter().map(Metadata::from).collect())
+            }
+            Value::Object(obj) => {
+                let iter = obj.into_iter().map(|(k, v)| (k, v.into()));
+                Metadata::Object(iter.collect())
+            }
+        }
+    }
+}
+
+impl From<Metadata> for Value {
+    fn from(value: Metadata) -> Self {
+        // Non-finite floats are not valid JSON numbers.
+        let float = |value: f64| match Number::from_f64(value) {
+            Some(number) => Value::Number(number),
+            None => Value::Null,
+        };
+
+        match value {
+            Metadata::Text(value) => Value::String(value),
+            Metadata::Integer(value) => Value::from(value),
+            Metadata::Float(value) => float(value as f64),
+            Metadata::Array(arr) => {
+                Value::Array(arr.into_iter().map(Value::from).collect())
+            }
+            Metadata::Object(obj) => {
+                let iter = obj.into_iter().map(|(k, v)| (k, v.into()));
+                Value::Object(iter.collect())
+            }
+            Metadata::Boolean(value) => Value::Bool(value),
+            Metadata::Null => Value::Null,
+            Metadata::Signed(value) => Value::from(value),
+            Metadata::Double(value) => float(value),
+            Metadata::Bytes(value) => Value::from(value),
+            Metadata::Timestamp(value) => Value::from(value),
+        }
+    }
+}
@@ -1,16 +1,46 @@
 /// The collection of vectors and their data.
 pub mod collection;
+/// The thread-safe collection for concurrent operations.
+pub mod concurrent;
+/// The disk-resident collection for memory-mapped search.
+pub mod disk;
 /// Error types for the database.
 pub mod err;
+/// Named vector fields of the records.
+pub mod field;
+/// Types for the metadata filters and indexes.
+pub mod filter;
 /// Types for the metadata.
 pub mod metadata;
+/// Schema to validate the metadata.
+pub mod schema;
+/// Full-text search over a metadata field.
+pub mod text;
 /// Types for the vectors.
 pub mod vector;
 
 // Internal modules.
+mod arena;
+mod convert;
+mod distance;
+mod flat;
+mod ivf;
+mod sparse;
+mod store;
 mod utils;
 
+use crate::db::wal::WriteAheadLog;
+use arena::{vector_map, VectorArena};
+use distance::*;
+use field::{Aggregation, VectorFields};
+use filter::{Filter, IndexKind, MetadataIndex};
+use flat::select_nearest;
+use ivf::IvfIndex;
 use metadata::*;
+use schema::*;
+use sparse::SparseIndex;
+use store::*;
+use text::{Fusion, ScoredResult, TextIndex};
 use utils::*;
 use vector::*;
 
@@ -21,6 +51,7 @@ use rand::random;
 use rayon::iter::*;
 use serde::{Deserialize, Serialize};
 use serde_big_array::BigArray;
+use sled::{Batch, Tree};
 use std::cmp::*;
 use std::collections::{BinaryHeap, HashMap};
 use std::error::Error;
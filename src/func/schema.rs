@@ -0,0 +1,231 @@
+use super::*;
+use std::fmt::{self, Display};
+
+/// The expected shape of the metadata object of the records.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Schema {
+    /// Declared fields of the metadata object by their name.
+    pub fields: HashMap<String, Field>,
+    /// Allows fields that are not declared in the schema.
+    pub allow_unknown: bool,
+}
+
+/// The declaration of a field in the schema.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Field {
+    /// Type of the field value.
+    pub kind: FieldType,
+    /// If false, the field can be missing or null.
+    pub required: bool,
+}
+
+/// The type of a field value in the schema.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum FieldType {
+    /// Any metadata value.
+    Any,
+    /// A text value.
+    Text,
+    /// An unsigned or a signed integer.
+    Integer,
+    /// Any number including integers.
+    Float,
+    /// A boolean value.
+    Boolean,
+    /// Raw bytes.
+    Bytes,
+    /// A timestamp value.
+    Timestamp,
+    /// An array with items of the same type.
+    Array(Box<FieldType>),
+    /// A nested object with its own schema.
+    Object(Schema),
+}
+
+impl Schema {
+    /// Creates an empty schema that rejects unknown fields.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares a field that must be present and not null.
+    /// * `name`: Name of the field.
+    /// * `kind`: Type of the field value.
+    pub fn required(mut self, name: &str, kind: FieldType) -> Self {
+        let field = Field { kind, required: true };
+        self.fields.insert(name.to_string(), field);
+        self
+    }
+
+    /// Declares a field that can be missing or null.
+    /// * `name`: Name of the field.
+    /// * `kind`: Type of the field value.
+    pub fn optional(mut self, name: &str, kind: FieldType) -> Self {
+        let field = Field { kind, required: false };
+        self.fields.insert(name.to_string(), field);
+        self
+    }
+
+    /// Sets whether fields not declared in the schema are allowed.
+    pub fn allow_unknown(mut self, allow: bool) -> Self {
+        self.allow_unknown = allow;
+        self
+    }
+
+    /// Validates the metadata against the schema.
+    /// * `data`: Metadata of a record.
+    pub fn validate(&self, data: &Metadata) -> Result<(), ValidationError> {
+        self.validate_object(data, "")
+    }
+
+    fn validate_object(
+        &self,
+        data: &Metadata,
+        path: &str,
+    ) -> Result<(), ValidationError> {
+        let obj = match data {
+            Metadata::Object(obj) => obj,
+            _ => {
+                let kind = FieldType::Object(self.clone());
+                return Err(ValidationError::invalid_type(path, &kind));
+            }
+        };
+
+        // Sorted to report the same field for the same metadata.
+        let mut fields = Vec::from_iter(self.fields.iter());
+        fields.sort_by_key(|(name, _)| *name);
+
+        for (name, field) in fields {
+            let path = join_path(path, name);
+            match obj.get(name) {
+                None | Some(Metadata::Null) if field.required => {
+                    return Err(ValidationError::new(
+                        &path,
+                        ValidationErrorKind::Missing,
+                    ));
+                }
+                None | Some(Metadata::Null) => {}
+                Some(value) => field.kind.validate(value, &path)?,
+            }
+        }
+
+        if !self.allow_unknown {
+            let mut names = Vec::from_iter(obj.keys());
+            names.sort();
+
+            if let Some(name) =
+                names.iter().find(|k| !self.fields.contains_key(**k))
+            {
+                let path = join_path(path, name);
+                return Err(ValidationError::new(
+                    &path,
+                    ValidationErrorKind::Unknown,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FieldType {
+    fn validate(
+        &self,
+        value: &Metadata,
+        path: &str,
+    ) -> Result<(), ValidationError> {
+        let is_valid = match (self, value) {
+            (FieldType::Any, _) => true,
+            (FieldType::Text, Metadata::Text(_)) => true,
+            (
+                FieldType::Integer,
+                Metadata::Integer(_) | Metadata::Signed(_),
+            ) => true,
+            (FieldType::Float, value) => value.as_f64().is_some(),
+            (FieldType::Boolean, Metadata::Boolean(_)) => true,
+            (FieldType::Bytes, Metadata::Bytes(_)) => true,
+            (FieldType::Timestamp, Metadata::Timestamp(_)) => true,
+            (FieldType::Array(kind), Metadata::Array(items)) => {
+                for (i, item) in items.iter().enumerate() {
+                    kind.validate(item, &join_path(path, &i.to_string()))?;
+                }
+
+                true
+            }
+            (FieldType::Object(schema), value) => {
+                return schema.validate_object(value, path);
+            }
+            _ => false,
+        };
+
+        match is_valid {
+            true => Ok(()),
+            false => Err(ValidationError::invalid_type(path, self)),
+        }
+    }
+}
+
+fn join_path(path: &str, name: &str) -> String {
+    match path.is_empty() {
+        true => name.to_string(),
+        false => format!("{}.{}", path, name),
+    }
+}
+
+/// The error when the metadata doesn't match the schema.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationError {
+    /// Path of the offending field like `author.name`.
+    /// Empty if the metadata itself is not an object.
+    pub path: String,
+    /// Reason why the field is invalid.
+    pub kind: ValidationErrorKind,
+}
+
+/// The reason why a field doesn't match the schema.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationErrorKind {
+    /// The required field is missing or null.
+    Missing,
+    /// The field is not declared in the schema.
+    Unknown,
+    /// The field value has a different type.
+    InvalidType(FieldType),
+}
+
+impl ValidationError {
+    fn new(path: &str, kind: ValidationErrorKind) -> Self {
+        Self { path: path.to_string(), kind }
+    }
+
+    fn invalid_type(path: &str, expected: &FieldType) -> Self {
+        Self::new(path, ValidationErrorKind::InvalidType(expected.clone()))
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let path = match self.path.is_empty() {
+            true => "The metadata",
+            false => &format!("The field `{}`", self.path),
+        };
+
+        match &self.kind {
+            ValidationErrorKind::Missing => write!(f, "{} is required.", path),
+            ValidationErrorKind::Unknown => {
+                write!(f, "{} is not in the schema.", path)
+            }
+            ValidationErrorKind::InvalidType(kind) => {
+                let kind = match kind {
+                    FieldType::Array(_) => "Array",
+                    FieldType::Object(_) => "Object",
+                    kind => &format!("{:?}", kind),
+                };
+
+                write!(f, "{} must be of type {}.", path, kind)
+            }
+        }
+    }
+}
+
+impl Error for ValidationError {}
@@ -0,0 +1,71 @@
+use super::*;
+
+/// The inverted index of the sparse vectors for dot product scoring.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SparseIndex {
+    /// Sparse vectors of the records.
+    vectors: HashMap<VectorID, SparseVector>,
+    /// Values of the records by their dimension.
+    postings: HashMap<u32, HashMap<VectorID, f32>>,
+}
+
+impl SparseIndex {
+    /// Returns the sparse vector of the record if any.
+    pub fn get(&self, id: &VectorID) -> Option<&SparseVector> {
+        self.vectors.get(id)
+    }
+
+    /// Returns true if no record has a sparse vector.
+    pub fn is_empty(&self) -> bool {
+        self.vectors.is_empty()
+    }
+
+    /// Adds or replaces the sparse vector of a record.
+    pub fn insert(&mut self, id: VectorID, vector: &SparseVector) {
+        self.remove(&id);
+        for (index, value) in vector.pairs() {
+            self.postings.entry(*index).or_default().insert(id, *value);
+        }
+
+        self.vectors.insert(id, vector.clone());
+    }
+
+    /// Removes the sparse vector of a record if any.
+    pub fn remove(&mut self, id: &VectorID) {
+        let vector = match self.vectors.remove(id) {
+            Some(vector) => vector,
+            None => return,
+        };
+
+        for (index, _) in vector.pairs() {
+            if let Some(postings) = self.postings.get_mut(index) {
+                postings.remove(id);
+                if postings.is_empty() {
+                    self.postings.remove(index);
+                }
+            }
+        }
+    }
+
+    /// Returns the IDs of the records with the highest dot products
+    /// with the query. Records sharing no dimension are excluded.
+    pub fn search(
+        &self,
+        query: &SparseVector,
+        n: usize,
+    ) -> Vec<(VectorID, f32)> {
+        let mut scores: HashMap<VectorID, f32> = HashMap::new();
+        for (index, value) in query.pairs() {
+            if let Some(postings) = self.postings.get(index) {
+                for (id, other) in postings.iter() {
+                    *scores.entry(*id).or_default() += value * other;
+                }
+            }
+        }
+
+        let mut scores = Vec::from_iter(scores);
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        scores.truncate(n);
+        scores
+    }
+}
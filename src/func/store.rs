@@ -0,0 +1,292 @@
+use super::*;
+use serde::{Deserializer, Serializer};
+use std::collections::HashSet;
+use std::sync::{Arc, Weak};
+
+/// The trees attached to the stores of the loaded collections.
+static ATTACHED: Mutex<Vec<Weak<AttachedTree>>> = const_mutex(Vec::new());
+
+/// The metadata of the vector records.
+///
+/// Without a tree, all of the metadata is kept in memory. When the
+/// collection is stored in the database, a tree is attached and the
+/// metadata is loaded from it on demand. In that case, the memory only
+/// keeps the changes since the collection was last saved.
+///
+/// The attached tree is shared with the database and the collections
+/// loaded from it. Before the tree is changed by saving another handle
+/// or dropped by deleting or renaming the stored collection, the other
+/// stores copy the metadata from it to keep reading the same metadata.
+#[derive(Default)]
+pub struct DataStore {
+    memory: RwLock<HashMap<VectorID, Metadata>>,
+    deleted: RwLock<HashSet<VectorID>>,
+    tree: Option<Arc<AttachedTree>>,
+}
+
+impl DataStore {
+    /// Returns the metadata associated with the vector ID.
+    pub fn get(&self, id: &VectorID) -> Result<Metadata, Box<dyn Error>> {
+        if let Some(data) = self.memory.read().get(id) {
+            return Ok(data.clone());
+        }
+
+        let tree = match &self.tree {
+            Some(tree) if !self.deleted.read().contains(id) => tree,
+            _ => return Err(err::RECORD_NOT_FOUND.into()),
+        };
+
+        match tree.get(id)? {
+            Some(data) => Ok(data),
+            None => Err(err::RECORD_NOT_FOUND.into()),
+        }
+    }
+
+    /// Inserts or replaces the metadata of the vector ID.
+    pub fn insert(&mut self, id: VectorID, data: Metadata) {
+        self.deleted.get_mut().remove(&id);
+        self.memory.get_mut().insert(id, data);
+    }
+
+    /// Removes the metadata of the vector ID.
+    pub fn remove(&mut self, id: &VectorID) {
+        self.memory.get_mut().remove(id);
+        if self.tree.is_some() {
+            self.deleted.get_mut().insert(*id);
+        }
+    }
+
+    /// Returns all of the metadata including the ones in the tree.
+    pub fn entries(&self) -> Result<Vec<(VectorID, Metadata)>, Box<dyn Error>> {
+        let memory = self.memory.read();
+        let deleted = self.deleted.read();
+
+        let mut entries = Vec::with_capacity(memory.len());
+        if let Some(tree) = &self.tree {
+            for (id, data) in tree.entries()? {
+                if !memory.contains_key(&id) && !deleted.contains(&id) {
+                    entries.push((id, data));
+                }
+            }
+        }
+
+        let iter = memory.iter().map(|(id, data)| (*id, data.clone()));
+        entries.extend(iter);
+        Ok(entries)
+    }
+
+    /// Attaches the tree to load the metadata from.
+    pub fn attach(&mut self, tree: Tree) {
+        self.tree = Some(AttachedTree::new(tree, None));
+    }
+
+    /// Returns the changes to write the metadata to the tree.
+    /// If the tree is not attached, the batch replaces its content.
+    /// The other stores attached to the tree are detached from it.
+    pub fn batch(&self, tree: &Tree) -> Result<Batch, Box<dyn Error>> {
+        Self::detach(tree, Some(self))?;
+        let mut batch = Batch::default();
+
+        // A detached store no longer knows the content of the tree.
+        let detached = self.tree.as_ref().is_some_and(|tree| tree.detached());
+        if self.is_attached(tree) && !detached {
+            for id in self.deleted.read().iter() {
+                batch.remove(&id.0.to_be_bytes());
+            }
+
+            for (id, data) in self.memory.read().iter() {
+                batch.insert(&id.0.to_be_bytes(), bincode::serialize(data)?);
+            }
+        } else {
+            for key in tree.iter().keys() {
+                batch.remove(key?);
+            }
+
+            for (id, data) in self.entries()? {
+                batch.insert(&id.0.to_be_bytes(), bincode::serialize(&data)?);
+            }
+        }
+
+        Ok(batch)
+    }
+
+    /// Clears the changes after they are written to the tree.
+    pub fn commit(&self, tree: &Tree) {
+        if self.is_attached(tree) {
+            self.commit_attached();
+        }
+    }
+
+    /// Clears the changes after they are written to the attached tree.
+    pub fn commit_attached(&self) {
+        let attached = match &self.tree {
+            Some(attached) => attached,
+            None => return,
+        };
+
+        // The tree has all of the metadata of the store now.
+        self.memory.write().clear();
+        self.deleted.write().clear();
+        *attached.detached.write() = None;
+    }
+
+    /// Returns a copy of the store attached to the same tree as this
+    /// store. Unlike a clone, saving the copy doesn't detach this store
+    /// so it must have the same changes as this store when it's saved.
+    pub fn share(&self) -> Self {
+        Self {
+            memory: RwLock::new(self.memory.read().clone()),
+            deleted: RwLock::new(self.deleted.read().clone()),
+            tree: self.tree.clone(),
+        }
+    }
+
+    /// Copies the metadata from the tree to the stores attached to it
+    /// before the tree is changed or dropped except for the store that
+    /// changes it.
+    /// * `tree`: Tree to be changed or dropped.
+    /// * `except`: Store writing its metadata to the tree.
+    pub fn detach(
+        tree: &Tree,
+        except: Option<&DataStore>,
+    ) -> Result<(), Box<dyn Error>> {
+        let except = except.and_then(|store| store.tree.as_ref());
+        let mut attached = ATTACHED.lock();
+        attached.retain(|weak| weak.strong_count() > 0);
+
+        for other in attached.iter().filter_map(Weak::upgrade) {
+            let excepted = except.is_some_and(|tree| Arc::ptr_eq(tree, &other));
+            if !excepted && same_tree(&other.tree, tree) {
+                other.detach()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the tree is the attached tree.
+    fn is_attached(&self, tree: &Tree) -> bool {
+        match &self.tree {
+            Some(attached) => same_tree(&attached.tree, tree),
+            None => false,
+        }
+    }
+}
+
+impl Clone for DataStore {
+    fn clone(&self) -> Self {
+        // The clone is detached separately from this store.
+        let tree = self.tree.as_ref().map(|attached| {
+            let detached = attached.detached.read().clone();
+            AttachedTree::new(attached.tree.clone(), detached)
+        });
+
+        Self {
+            memory: RwLock::new(self.memory.read().clone()),
+            deleted: RwLock::new(self.deleted.read().clone()),
+            tree,
+        }
+    }
+}
+
+/// The tree attached to a store with the metadata copied from it when
+/// it's detached.
+struct AttachedTree {
+    tree: Tree,
+    detached: RwLock<Option<HashMap<VectorID, Metadata>>>,
+}
+
+impl AttachedTree {
+    /// Registers the tree to be detached before it's changed.
+    fn new(
+        tree: Tree,
+        detached: Option<HashMap<VectorID, Metadata>>,
+    ) -> Arc<Self> {
+        let attached = Arc::new(Self { tree, detached: RwLock::new(detached) });
+        ATTACHED.lock().push(Arc::downgrade(&attached));
+        attached
+    }
+
+    /// Returns true if the metadata is copied from the tree.
+    fn detached(&self) -> bool {
+        self.detached.read().is_some()
+    }
+
+    /// Returns the stored metadata of the vector ID if any.
+    fn get(&self, id: &VectorID) -> Result<Option<Metadata>, Box<dyn Error>> {
+        if let Some(detached) = self.detached.read().as_ref() {
+            return Ok(detached.get(id).cloned());
+        }
+
+        match self.tree.get(id.0.to_be_bytes())? {
+            Some(value) => Ok(Some(bincode::deserialize(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns all of the stored metadata.
+    fn entries(&self) -> Result<Vec<(VectorID, Metadata)>, Box<dyn Error>> {
+        if let Some(detached) = self.detached.read().as_ref() {
+            let iter = detached.iter().map(|(id, data)| (*id, data.clone()));
+            return Ok(iter.collect());
+        }
+
+        self.read_tree()
+    }
+
+    /// Copies the metadata from the tree if it's not copied yet.
+    fn detach(&self) -> Result<(), Box<dyn Error>> {
+        let mut detached = self.detached.write();
+        if detached.is_none() {
+            *detached = Some(self.read_tree()?.into_iter().collect());
+        }
+
+        Ok(())
+    }
+
+    /// Reads all of the metadata stored in the tree.
+    fn read_tree(&self) -> Result<Vec<(VectorID, Metadata)>, Box<dyn Error>> {
+        let mut entries = Vec::with_capacity(self.tree.len());
+        for entry in self.tree.iter() {
+            let (key, value) = entry?;
+            let id = VectorID(u32::from_be_bytes(key.as_ref().try_into()?));
+            entries.push((id, bincode::deserialize(&value)?));
+        }
+
+        Ok(entries)
+    }
+}
+
+/// Returns true if both are the same tree. The database returns the
+/// same tree instance for the same name so a tree with the same name
+/// in another database isn't the same tree.
+fn same_tree(a: &Tree, b: &Tree) -> bool {
+    std::ptr::eq(a.deref(), b.deref())
+}
+
+impl From<HashMap<VectorID, Metadata>> for DataStore {
+    fn from(memory: HashMap<VectorID, Metadata>) -> Self {
+        Self { memory: RwLock::new(memory), ..Default::default() }
+    }
+}
+
+// The store is serialized as the metadata map in memory to keep the
+// format of the collections stored before the metadata tree existed.
+
+impl Serialize for DataStore {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.memory.read().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DataStore {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let memory = HashMap::deserialize(deserializer)?;
+        Ok(Self::from(memory))
+    }
+}
@@ -0,0 +1,191 @@
+use super::*;
+use std::collections::HashSet;
+
+/// BM25 term frequency saturation parameter.
+const K1: f32 = 1.2;
+/// BM25 document length normalization parameter.
+const B: f32 = 0.75;
+
+/// The method to combine the vector search results with the text or
+/// the sparse vector search results.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Fusion {
+    /// Reciprocal rank fusion with the rank constant. Each result
+    /// scores `1 / (k + rank)` in each of the result lists. The
+    /// commonly used constant is 60.
+    Reciprocal(f32),
+    /// Weighted sum of the scores normalized to the range of 0 to 1.
+    /// The value is the weight of the vector score which must be in
+    /// [0, 1] and the text or sparse score is weighted by the remainder.
+    Weighted(f32),
+}
+
+impl Fusion {
+    /// Returns an error if the weight is not in the range of 0 to 1.
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        match *self {
+            Fusion::Weighted(weight) if !(0.0..=1.0).contains(&weight) => {
+                Err(err::FUSION_WEIGHT_INVALID.into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for Fusion {
+    /// Reciprocal rank fusion with the rank constant of 60.
+    fn default() -> Self {
+        Fusion::Reciprocal(60.0)
+    }
+}
+
+/// The collection text or hybrid search result.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScoredResult {
+    /// Vector ID.
+    pub id: u32,
+    /// Relevance score of the record. Higher is better.
+    pub score: f32,
+    /// Data associated with the vector.
+    pub data: Metadata,
+}
+
+/// Splits the text into lowercase alphanumeric terms.
+/// * `text`: Text to tokenize.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(|term| term.to_lowercase())
+        .collect()
+}
+
+/// The inverted index of a text field for BM25 scoring.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub(crate) struct TextIndex {
+    /// Term frequencies in the records by their term.
+    postings: HashMap<String, HashMap<VectorID, u32>>,
+    /// Number of terms in the records.
+    lengths: HashMap<VectorID, u32>,
+    /// Sum of the number of terms of all records.
+    total_length: u64,
+}
+
+impl TextIndex {
+    /// Adds the text of a record to the index.
+    pub fn insert(&mut self, id: VectorID, text: &str) {
+        let terms = tokenize(text);
+        if terms.is_empty() {
+            return;
+        }
+
+        self.lengths.insert(id, terms.len() as u32);
+        self.total_length += terms.len() as u64;
+        for term in terms {
+            let postings = self.postings.entry(term).or_default();
+            *postings.entry(id).or_default() += 1;
+        }
+    }
+
+    /// Removes the text of a record from the index.
+    pub fn remove(&mut self, id: &VectorID, text: &str) {
+        let length = match self.lengths.remove(id) {
+            Some(length) => length,
+            None => return,
+        };
+
+        self.total_length -= length as u64;
+        for term in tokenize(text) {
+            if let Some(postings) = self.postings.get_mut(&term) {
+                postings.remove(id);
+                if postings.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+    }
+
+    /// Returns the IDs of the records with the highest BM25 scores
+    /// for the query. Records without any of the terms are excluded.
+    pub fn search(&self, query: &str, n: usize) -> Vec<(VectorID, f32)> {
+        if self.lengths.is_empty() {
+            return vec![];
+        }
+
+        let count = self.lengths.len() as f32;
+        let average_length = self.total_length as f32 / count;
+        let terms: HashSet<String> = HashSet::from_iter(tokenize(query));
+
+        let mut scores: HashMap<VectorID, f32> = HashMap::new();
+        for term in terms.iter() {
+            let postings = match self.postings.get(term) {
+                Some(postings) => postings,
+                None => continue,
+            };
+
+            let frequency = postings.len() as f32;
+            let idf =
+                (1.0 + (count - frequency + 0.5) / (frequency + 0.5)).ln();
+
+            for (id, tf) in postings.iter() {
+                let tf = *tf as f32;
+                let length = self.lengths[id] as f32 / average_length;
+                let norm = K1 * (1.0 - B + B * length);
+                *scores.entry(*id).or_default() +=
+                    idf * tf * (K1 + 1.0) / (tf + norm);
+            }
+        }
+
+        let mut scores = Vec::from_iter(scores);
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        scores.truncate(n);
+        scores
+    }
+}
+
+/// Combines the vector and the scored search results into one ranking.
+/// * `nearest`: Vector IDs and distances sorted by the distance.
+/// * `matches`: Vector IDs and scores sorted by the score.
+/// * `fusion`: Method to combine the results.
+pub(crate) fn fuse(
+    nearest: &[(VectorID, f32)],
+    matches: &[(VectorID, f32)],
+    fusion: &Fusion,
+) -> Vec<(VectorID, f32)> {
+    let mut scores: HashMap<VectorID, f32> = HashMap::new();
+
+    match *fusion {
+        Fusion::Reciprocal(k) => {
+            for results in [nearest, matches] {
+                for (rank, (id, _)) in results.iter().enumerate() {
+                    let score = 1.0 / (k + rank as f32 + 1.0);
+                    *scores.entry(*id).or_default() += score;
+                }
+            }
+        }
+        Fusion::Weighted(weight) => {
+            // The closest vector scores 1 and the farthest 0.
+            let distances = nearest.iter().map(|(_, distance)| *distance);
+            let min = distances.clone().fold(f32::INFINITY, f32::min);
+            let max = distances.fold(f32::NEG_INFINITY, f32::max);
+            for (id, distance) in nearest.iter() {
+                let score = match max > min {
+                    true => (max - distance) / (max - min),
+                    false => 1.0,
+                };
+
+                *scores.entry(*id).or_default() += weight * score;
+            }
+
+            // The best match scores 1.
+            let top = matches.first().map_or(0.0, |(_, score)| *score);
+            for (id, score) in matches.iter() {
+                let score = if top > 0.0 { score / top } else { 0.0 };
+                *scores.entry(*id).or_default() += (1.0 - weight) * score;
+            }
+        }
+    }
+
+    let mut scores = Vec::from_iter(scores);
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    scores
+}
@@ -5,6 +5,12 @@ pub const INVALID: VectorID = VectorID(u32::MAX);
 /// The M value for the HNSW algorithm.
 pub const M: usize = 32;
 
+/// Storage of the vectors that the graph traversal reads from.
+pub trait VectorStore {
+    /// Returns the vector values or None if the vector is deleted.
+    fn get_vector(&self, vector_id: &VectorID) -> Option<&[f32]>;
+}
+
 pub trait Layer {
     type Slice: Deref<Target = [VectorID]>;
     fn nearest_iter(&self, vector_id: &VectorID) -> NearestIter<Self::Slice>;
@@ -166,7 +172,12 @@ impl UpperNode {
 impl<'a> Layer for &'a [UpperNode] {
     type Slice = &'a [VectorID];
     fn nearest_iter(&self, vector_id: &VectorID) -> NearestIter<Self::Slice> {
-        NearestIter::new(&self[vector_id.0 as usize].0)
+        // Vectors inserted after the index is built are only
+        // linked in the base layer.
+        match self.get(vector_id.0 as usize) {
+            Some(node) => NearestIter::new(&node.0),
+            None => NearestIter::new(&[]),
+        }
     }
 }
 
@@ -216,8 +227,7 @@ impl Visited {
             return;
         }
 
-        self.store.clear();
-        self.store.resize(self.store.len(), 0);
+        self.store.fill(0);
         self.generation = 1;
     }
 }
@@ -234,6 +244,10 @@ pub struct Candidate {
 pub struct Search {
     pub ef: usize,
     pub visited: Visited,
+    /// Number of distances computed since the last reset.
+    pub distances: usize,
+    /// Number of nodes whose neighbors were scanned.
+    pub expanded: usize,
     candidates: BinaryHeap<Reverse<Candidate>>,
     nearest: Vec<Candidate>,
     working: Vec<Candidate>,
@@ -247,11 +261,11 @@ impl Search {
     }
 
     /// Searches the nearest neighbors in the graph layer.
-    pub fn search<L: Layer>(
+    pub fn search<L: Layer, V: VectorStore + ?Sized>(
         &mut self,
         layer: L,
-        vector: &Vector,
-        vectors: &HashMap<VectorID, Vector>,
+        vector: &[f32],
+        vectors: &V,
         links: usize,
     ) {
         while let Some(Reverse(candidate)) = self.candidates.pop() {
@@ -262,6 +276,7 @@ impl Search {
                 }
             }
 
+            self.expanded += 1;
             let layer_iter = layer.nearest_iter(&candidate.vector_id);
             for vector_id in layer_iter.take(links) {
                 self.push(&vector_id, vector, vectors);
@@ -272,18 +287,25 @@ impl Search {
     }
 
     /// Pushes a new neighbor candidate to the search object.
-    pub fn push(
+    pub fn push<V: VectorStore + ?Sized>(
         &mut self,
         vector_id: &VectorID,
-        vector: &Vector,
-        vectors: &HashMap<VectorID, Vector>,
+        vector: &[f32],
+        vectors: &V,
     ) {
         if !self.visited.insert(vector_id) {
             return;
         }
 
-        let other = &vectors[vector_id];
-        let distance = OrderedFloat::from(vector.distance(other));
+        // The vector might have been deleted while it's still
+        // referenced by the neighbors of other nodes.
+        let other = match vectors.get_vector(vector_id) {
+            Some(vector) => vector,
+            None => return,
+        };
+
+        self.distances += 1;
+        let distance = OrderedFloat::from(squared_euclidean(vector, other));
         let new = Candidate { distance, vector_id: *vector_id };
 
         // Make sure the index to insert to is within the EF scope.
@@ -317,6 +339,8 @@ impl Search {
         self.nearest.clear();
         self.working.clear();
         self.discarded.clear();
+        self.distances = 0;
+        self.expanded = 0;
     }
 
     /// Selects the nearest neighbors.
@@ -337,6 +361,8 @@ impl Default for Search {
             nearest: Vec::new(),
             working: Vec::new(),
             discarded: Vec::new(),
+            distances: 0,
+            expanded: 0,
             ef: 5,
         }
     }
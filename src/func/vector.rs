@@ -1,8 +1,10 @@
 use super::*;
+use std::fmt::{self, Display};
 
 /// The ID of a vector record.
 #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 #[derive(Eq, PartialEq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
 pub struct VectorID(pub u32);
 
 impl VectorID {
@@ -40,11 +42,37 @@ impl Vector {
         self.0.is_empty()
     }
 
+    /// Returns an error if the vector has NaN or infinite values.
+    pub fn validate(&self) -> Result<(), NonFiniteVectorError> {
+        match self.0.iter().position(|value| !value.is_finite()) {
+            Some(index) => Err(NonFiniteVectorError { index }),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the vector scaled to the unit length.
+    /// The zero vector is returned as is.
+    pub fn normalize(&self) -> Self {
+        let norm = self.0.iter().map(|value| value * value).sum::<f32>().sqrt();
+        if norm == 0.0 {
+            return self.clone();
+        }
+
+        self.0.iter().map(|value| value / norm).collect::<Vec<f32>>().into()
+    }
+
+    /// Returns true if the vectors have the same bit patterns.
+    /// Unlike the equality, NaN is identical to itself and the
+    /// positive zero is different from the negative zero.
+    pub fn is_identical(&self, other: &Self) -> bool {
+        let mut iter = self.0.iter().zip(other.0.iter());
+        let is_identical = |(a, b): (&f32, &f32)| a.to_bits() == b.to_bits();
+        self.len() == other.len() && iter.all(is_identical)
+    }
+
     /// Returns the Euclidean distance between two vectors.
     pub fn distance(&self, other: &Self) -> f32 {
-        assert_eq!(self.0.len(), other.0.len());
-        let iter = self.0.iter().zip(other.0.iter());
-        iter.map(|(a, b)| (a - b).powi(2)).sum::<f32>().sqrt()
+        euclidean(&self.0, &other.0)
     }
 
     /// Generates a random vector for testing.
@@ -78,3 +106,115 @@ impl From<&Vec<f32>> for Vector {
         Vector(vec.clone())
     }
 }
+
+impl From<&[f32]> for Vector {
+    fn from(slice: &[f32]) -> Self {
+        Vector(slice.to_vec())
+    }
+}
+
+/// The sparse vector embedding of index and value pairs like the
+/// SPLADE embeddings. The pairs are sorted by the index and the
+/// values of duplicate indexes are summed.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(from = "Vec<(u32, f32)>")]
+pub struct SparseVector(Vec<(u32, f32)>);
+
+impl SparseVector {
+    /// Creates a sparse vector from the index and value pairs.
+    /// * `pairs`: Indexes and values of the non-zero dimensions.
+    pub fn new(pairs: &[(u32, f32)]) -> Self {
+        let mut pairs = pairs.to_vec();
+        pairs.sort_by_key(|(index, _)| *index);
+
+        let mut merged: Vec<(u32, f32)> = Vec::with_capacity(pairs.len());
+        for (index, value) in pairs {
+            match merged.last_mut() {
+                Some(last) if last.0 == index => last.1 += value,
+                _ => merged.push((index, value)),
+            }
+        }
+
+        SparseVector(merged)
+    }
+
+    /// Returns the number of non-zero dimensions.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if the vector has no non-zero dimensions.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the index and value pairs sorted by the index.
+    pub fn pairs(&self) -> &[(u32, f32)] {
+        &self.0
+    }
+
+    /// Returns an error if the vector has NaN or infinite values.
+    /// The index of the error is the dimension of the value.
+    pub fn validate(&self) -> Result<(), NonFiniteVectorError> {
+        match self.0.iter().find(|(_, value)| !value.is_finite()) {
+            Some((index, _)) => {
+                Err(NonFiniteVectorError { index: *index as usize })
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the dot product of two sparse vectors.
+    pub fn dot(&self, other: &Self) -> f32 {
+        let (mut i, mut j) = (0, 0);
+        let mut sum = 0.0;
+
+        while i < self.0.len() && j < other.0.len() {
+            let (a, b) = (self.0[i], other.0[j]);
+            match a.0.cmp(&b.0) {
+                Ordering::Less => i += 1,
+                Ordering::Greater => j += 1,
+                Ordering::Equal => {
+                    sum += a.1 * b.1;
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+
+        sum
+    }
+
+    /// Generates a random sparse vector for testing.
+    /// * `dimension`: Number of possible dimensions.
+    /// * `len`: Maximum number of non-zero dimensions.
+    pub fn random(dimension: u32, len: usize) -> Self {
+        let pairs = (0..len)
+            .map(|_| (random::<u32>() % dimension, random::<f32>()))
+            .collect::<Vec<_>>();
+        Self::new(&pairs)
+    }
+}
+
+impl From<Vec<(u32, f32)>> for SparseVector {
+    fn from(pairs: Vec<(u32, f32)>) -> Self {
+        Self::new(&pairs)
+    }
+}
+
+/// The error when a vector has NaN or infinite values. These values
+/// can't be compared by distance so they are rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NonFiniteVectorError {
+    /// Index of the first non-finite value in the vector.
+    pub index: usize,
+}
+
+impl Display for NonFiniteVectorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let index = self.index;
+        write!(f, "The vector value at index {} is not finite.", index)
+    }
+}
+
+impl Error for NonFiniteVectorError {}
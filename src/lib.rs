@@ -14,6 +14,13 @@ mod func;
 pub mod prelude;
 
 pub use db::database;
+pub use db::wal;
 pub use func::collection;
+pub use func::concurrent;
+pub use func::disk;
+pub use func::field;
+pub use func::filter;
 pub use func::metadata;
+pub use func::schema;
+pub use func::text;
 pub use func::vector;
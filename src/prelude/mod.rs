@@ -1,4 +1,10 @@
 pub use crate::database::*;
 pub use crate::func::collection::*;
+pub use crate::func::concurrent::*;
+pub use crate::func::disk::*;
+pub use crate::func::field::*;
+pub use crate::func::filter::*;
 pub use crate::func::metadata::*;
+pub use crate::func::schema::*;
+pub use crate::func::text::*;
 pub use crate::func::vector::*;
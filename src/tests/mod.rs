@@ -1,8 +1,25 @@
+// Keep the original tests as they are written.
+#![allow(clippy::bool_assert_comparison, clippy::needless_borrow)]
+
 mod test_collection;
+mod test_concurrent;
 mod test_database;
+mod test_field;
+mod test_filter;
+mod test_metadata;
+mod test_schema;
+mod test_sparse;
+mod test_text;
+mod test_vector;
 
 use crate::collection::*;
+use crate::concurrent::*;
 use crate::database::*;
+use crate::field::Aggregation;
+use crate::filter::{Filter, IndexKind};
+use crate::metadata::Metadata;
+use crate::schema::{FieldType, Schema};
+use crate::text::{self, Fusion};
 use crate::vector::*;
 use rayon::iter::*;
 use std::collections::HashMap;
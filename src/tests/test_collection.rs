@@ -86,6 +86,46 @@ fn update() {
     assert_eq!(collection.get(&id).unwrap().data, record.data);
 }
 
+#[test]
+fn update_data_keeps_graph() {
+    let records = Record::many_random(DIMENSION, LEN);
+    let mut collection = create_collection(&records);
+
+    let query = Vector::random(DIMENSION);
+    let expected = collection.search_ids(&query, 10).unwrap();
+
+    // Neither of the updates changes the vector index.
+    let id = VectorID(5);
+    collection.update_data(&id, &"updated".into()).unwrap();
+    let record = Record::new(&records[6].vector, &"same vector".into());
+    collection.update(&VectorID(6), &record).unwrap();
+
+    assert_eq!(collection.search_ids(&query, 10).unwrap(), expected);
+    assert_eq!(collection.get(&id).unwrap().data, "updated".into());
+    assert_eq!(collection.get(&VectorID(6)).unwrap().data, record.data);
+    assert!(collection.update_data(&LEN.into(), &"x".into()).is_err());
+}
+
+#[test]
+fn patch_data() {
+    let records = Record::many_random(DIMENSION, LEN);
+    let mut collection = create_collection(&records);
+
+    let id = VectorID(5);
+    let data = HashMap::from([("title", "Hello"), ("author", "Elijah")]);
+    collection.update_data(&id, &data.into()).unwrap();
+
+    let patch = HashMap::from([
+        ("title", Metadata::from("Updated")),
+        ("author", Metadata::Null),
+    ]);
+
+    collection.patch_data(&id, &patch.into()).unwrap();
+
+    let expected = HashMap::from([("title", "Updated")]).into();
+    assert_eq!(collection.get(&id).unwrap().data, expected);
+}
+
 #[test]
 fn search() {
     let len = 1000;
@@ -109,6 +149,112 @@ fn search() {
     assert_eq!(distances.contains(&result[0].distance), true);
 }
 
+#[test]
+fn search_flat() {
+    let records = Record::many_random(DIMENSION, LEN);
+    let config = Config { index: IndexType::Flat, ..Default::default() };
+    let mut collection = Collection::build(&config, &records).unwrap();
+
+    // Change the collection after it's built.
+    collection.insert(&Record::random(DIMENSION)).unwrap();
+    collection.delete(&VectorID(3)).unwrap();
+    collection.update(&VectorID(5), &Record::random(DIMENSION)).unwrap();
+
+    // The flat search is exact.
+    let query = Vector::random(DIMENSION);
+    let result = collection.search(&query, 10).unwrap();
+    let truth = collection.true_search(&query, 10).unwrap();
+    let ids: Vec<u32> = result.iter().map(|r| r.id).collect();
+    let expected: Vec<u32> = truth.iter().map(|r| r.id).collect();
+    assert_eq!(ids, expected);
+    assert!(!ids.contains(&3));
+}
+
+#[test]
+fn search_ivf() {
+    let len = 1000;
+    let records = Record::many_random(DIMENSION, len);
+    let index = IndexType::Ivf { lists: 16, probes: 4 };
+    let config = Config { index, ..Default::default() };
+    let mut collection = Collection::build(&config, &records).unwrap();
+
+    collection.insert(&Record::random(DIMENSION)).unwrap();
+    collection.delete(&VectorID(3)).unwrap();
+    collection.update(&VectorID(5), &Record::random(DIMENSION)).unwrap();
+
+    // Scanning all of the lists is exact.
+    let query = Vector::random(DIMENSION);
+    let result = collection.search_with_probes(&query, 10, 16).unwrap();
+    let truth = collection.true_search(&query, 10).unwrap();
+    let ids: Vec<u32> = result.iter().map(|r| r.id).collect();
+    let expected: Vec<u32> = truth.iter().map(|r| r.id).collect();
+    assert_eq!(ids, expected);
+
+    // The default probes only scan some of the lists.
+    let result = collection.search(&query, 10).unwrap();
+    assert_eq!(result.len(), 10);
+    assert!(result.iter().all(|r| r.id != 3));
+}
+
+#[test]
+fn search_ivf_incremental() {
+    let index = IndexType::Ivf { lists: 4, probes: 4 };
+    let config = Config { index, ..Default::default() };
+    let mut collection = Collection::new(&config);
+
+    // The first vectors become the centroids of the empty index.
+    let records = Record::many_random(DIMENSION, LEN);
+    for record in records.iter() {
+        collection.insert(record).unwrap();
+    }
+
+    let query = &records[7].vector;
+    let result = collection.search(query, 1).unwrap();
+    assert_eq!(result[0].id, 7);
+
+    // The search isn't supported by the other index types.
+    let collection = create_collection(&records);
+    assert!(collection.search_with_probes(query, 1, 1).is_err());
+}
+
+#[test]
+fn search_ivf_incremental_recall() {
+    let index = IndexType::Ivf { lists: 16, probes: 4 };
+    let config = Config { index, ..Default::default() };
+
+    // Clustered vectors inserted by cluster so that the first vectors
+    // inserted into the empty index are all from the same cluster.
+    let centers: Vec<Vector> =
+        (0..16).map(|_| Vector::random(DIMENSION)).collect();
+    let near = |center: &Vector| {
+        let noise = Vector::random(DIMENSION);
+        let iter = center.0.iter().zip(noise.0.iter());
+        Vector(iter.map(|(c, n)| c + n * 0.1).collect())
+    };
+
+    let records: Vec<Record> = (0..2000)
+        .map(|i| Record::new(&near(&centers[i / 125]), &i.into()))
+        .collect();
+
+    let mut collection = Collection::new(&config);
+    for record in records.iter() {
+        collection.insert(record).unwrap();
+    }
+
+    // The centroids are retrained as the index grows so the search
+    // is close to the search of an index built at once instead of
+    // scanning the few lists that the vectors are crowded into.
+    let queries: Vec<Vector> = centers.iter().map(near).collect();
+    let built = Collection::build(&config, &records).unwrap();
+    let expected = built.evaluate_recall(&queries, 10).unwrap().recall;
+    let report = collection.evaluate_recall(&queries, 10).unwrap();
+    assert!(report.recall >= expected - 0.1);
+
+    let expected = built.search_counters().distances;
+    let distances = collection.search_counters().distances;
+    assert!(distances * 2 < expected * 3);
+}
+
 #[test]
 fn get() {
     let records = Record::many_random(DIMENSION, LEN);
@@ -121,3 +267,385 @@ fn get() {
     assert_eq!(record.data, records[5].data);
     assert_eq!(record.vector, records[5].vector);
 }
+
+#[test]
+fn index_vector() {
+    let records = Record::many_random(DIMENSION, LEN);
+    let mut collection = create_collection(&records);
+
+    // The vector is borrowed from the collection without a copy.
+    let id = VectorID(5);
+    assert_eq!(&collection[&id], records[5].vector.0.as_slice());
+    assert_eq!(collection.get_vector(&id), Some(&collection[&id]));
+
+    let record = Record::random(DIMENSION);
+    collection.update(&id, &record).unwrap();
+    assert_eq!(&collection[&id], record.vector.0.as_slice());
+    collection.delete(&id).unwrap();
+    assert_eq!(collection.get_vector(&id), None);
+}
+
+#[test]
+fn search_after_delete() {
+    let len = 1000;
+    let records = Record::many_random(DIMENSION, len);
+    let mut collection = create_collection(&records);
+
+    // Delete records that are linked in the upper layers.
+    for i in (0..len).step_by(3) {
+        collection.delete(&i.into()).unwrap();
+    }
+
+    let query = Vector::random(DIMENSION);
+    let result = collection.search(&query, 5).unwrap();
+
+    assert_eq!(result.len(), 5);
+    assert!(result.iter().all(|r| r.id % 3 != 0));
+}
+
+#[test]
+fn search_ids() {
+    let records = Record::many_random(DIMENSION, LEN);
+    let collection = create_collection(&records);
+
+    let query = Vector::random(DIMENSION);
+    let ids = collection.search_ids(&query, 5).unwrap();
+    let result = collection.search(&query, 5).unwrap();
+
+    assert_eq!(ids.len(), 5);
+    for ((id, distance), res) in ids.iter().zip(result.iter()) {
+        assert_eq!(id.0, res.id);
+        assert_eq!(*distance, res.distance);
+    }
+}
+
+#[test]
+fn reject_non_finite_vectors() {
+    let mut records = Record::many_random(DIMENSION, LEN);
+    let mut collection = create_collection(&records);
+
+    let mut vector = Vector::random(DIMENSION);
+    vector.0[3] = f32::NAN;
+    let record = Record::new(&vector, &5usize.into());
+
+    let error = collection.insert(&record).unwrap_err();
+    let error = error.downcast_ref::<NonFiniteVectorError>().unwrap();
+    assert_eq!(error.index, 3);
+    assert!(collection.update(&VectorID(0), &record).is_err());
+    assert!(collection.search(&vector, 5).is_err());
+    assert!(collection.true_search(&vector, 5).is_err());
+    assert_eq!(collection.len(), LEN);
+
+    records[50].vector.0[0] = f32::INFINITY;
+    assert!(Collection::build(&Config::default(), &records).is_err());
+}
+
+#[test]
+fn reject_first_record() {
+    let mut collection = Collection::new(&Config::default());
+    let mut vector = Vector::random(DIMENSION);
+    vector.0[0] = f32::NAN;
+    let record = Record::new(&vector, &0usize.into());
+    assert!(collection.insert(&record).is_err());
+
+    // The rejected record doesn't set the dimension.
+    assert_eq!(collection.dimension(), 0);
+    collection.insert(&Record::random(64)).unwrap();
+    assert_eq!(collection.dimension(), 64);
+}
+
+#[test]
+fn normalize_vectors() {
+    let config = Config { normalize: true, ..Default::default() };
+    let records = Record::many_random(DIMENSION, LEN);
+    let mut collection = Collection::build(&config, &records).unwrap();
+    collection.insert(&Record::random(DIMENSION)).unwrap();
+
+    let vector = collection.get(&VectorID(LEN as u32)).unwrap().vector;
+    let norm = vector.0.iter().map(|x| x * x).sum::<f32>().sqrt();
+    assert!((norm - 1.0).abs() < 1e-5);
+
+    // The query is normalized so its scale doesn't matter.
+    let query = records[7].vector.0.iter().map(|x| x * 3.0).collect::<Vec<_>>();
+    let result = collection.search(&query.into(), 1).unwrap();
+    assert_eq!(result[0].id, 7);
+    assert!(result[0].distance < 1e-5);
+}
+
+#[test]
+fn search_mmr() {
+    let mut records = Record::many_random(DIMENSION, LEN);
+    let query = Vector::random(DIMENSION);
+    for _ in 0..5 {
+        records.push(Record::new(&query, &Metadata::Null));
+    }
+
+    let collection = create_collection(&records);
+    let duplicate = |id: u32| id as usize >= LEN;
+
+    // Only the relevance ranks the duplicates first.
+    let result = collection.search_mmr(&query, 5, 50, 1.0).unwrap();
+    assert!(result.iter().all(|r| duplicate(r.id)));
+
+    // The diversity skips the other duplicates after the first one.
+    let result = collection.search_mmr(&query, 5, 50, 0.5).unwrap();
+    assert_eq!(result.len(), 5);
+    assert!(duplicate(result[0].id));
+    assert!(result[1..].iter().all(|r| !duplicate(r.id)));
+
+    assert!(collection.search_mmr(&query, 5, 50, 1.5).is_err());
+}
+
+#[test]
+fn search_grouped() {
+    // Each document is split into ten chunks.
+    let records: Vec<Record> = (0..LEN)
+        .map(|i| {
+            let document = i / 10;
+            let vector = Vector::random(DIMENSION);
+            let data = HashMap::from([("document", document)]).into();
+            Record::new(&vector, &data)
+        })
+        .collect();
+
+    let collection = create_collection(&records);
+    let query = Vector::random(DIMENSION);
+    let result = collection.search_grouped(&query, 4, 2, "document").unwrap();
+
+    assert_eq!(result.len(), 4);
+    for group in result.iter() {
+        assert!(!group.results.is_empty() && group.results.len() <= 2);
+        for record in group.results.iter() {
+            assert_eq!(record.data.get("document"), Some(&group.value));
+        }
+    }
+
+    // More groups than the search finds at first widens the search.
+    let groups = LEN / 10;
+    let result = collection.search_grouped(&query, groups, 1, "document");
+    assert_eq!(result.unwrap().len(), groups);
+    let result = collection.search_grouped(&query, 4, 1, "unknown");
+    assert!(result.unwrap().is_empty());
+}
+
+#[test]
+fn search_grouped_empty() {
+    let records: Vec<Record> = (0..LEN)
+        .map(|i| HashMap::from([("document", i / 10)]).into())
+        .map(|data| Record::new(&Vector::random(DIMENSION), &data))
+        .collect();
+
+    let config = Config { ef_search: 0, ..Default::default() };
+    let collection = Collection::build(&config, &records).unwrap();
+    let query = Vector::random(DIMENSION);
+
+    let result = collection.search_grouped(&query, 4, 0, "document");
+    assert!(result.unwrap().is_empty());
+    let result = collection.search_grouped(&query, 0, 2, "document");
+    assert!(result.unwrap().is_empty());
+}
+
+#[test]
+fn iter_records() {
+    let records = Record::many_random(DIMENSION, 10);
+    let mut collection = create_collection(&records);
+    collection.delete(&VectorID(3)).unwrap();
+
+    let items = collection.records().unwrap();
+    assert_eq!(items.len(), 9);
+    assert_eq!(items[3].0, VectorID(4));
+    assert_eq!(items[3].1.data, records[4].data);
+    assert_eq!(collection.iter().count(), 9);
+}
+
+#[test]
+fn scan() {
+    let mut collection = create_collection(&Record::many_random(DIMENSION, 10));
+
+    let page = collection.scan(None, 4).unwrap();
+    assert_eq!(page.records.len(), 4);
+    assert_eq!(page.next, Some(VectorID(4)));
+
+    // The changes after the cursor are visible in the next pages.
+    collection.delete(&VectorID(4)).unwrap();
+    collection.insert(&Record::random(DIMENSION)).unwrap();
+
+    let page = collection.scan(page.next, 4).unwrap();
+    let ids: Vec<u32> = page.records.iter().map(|(id, _)| id.0).collect();
+    assert_eq!(ids, vec![5, 6, 7, 8]);
+
+    let page = collection.scan(page.next, 4).unwrap();
+    let ids: Vec<u32> = page.records.iter().map(|(id, _)| id.0).collect();
+    assert_eq!(ids, vec![9, 10]);
+    assert_eq!(page.next, None);
+}
+
+#[test]
+fn search_with_offset() {
+    let records = Record::many_random(DIMENSION, LEN);
+    let collection = create_collection(&records);
+    let query = Vector::random(DIMENSION);
+
+    // A page is the slice of the search for all of the neighbors
+    // up to the end of the page.
+    for offset in (0..30).step_by(10) {
+        let page = collection.search_with_offset(&query, offset, 10).unwrap();
+        let all = collection.search_with_offset(&query, 0, offset + 10);
+        let ids: Vec<u32> = page.iter().map(|r| r.id).collect();
+        let expected: Vec<u32> =
+            all.unwrap()[offset..].iter().map(|r| r.id).collect();
+        assert_eq!(ids, expected);
+        assert_eq!(ids.len(), 10);
+    }
+}
+
+#[test]
+fn search_with_offset_pages() {
+    let records = Record::many_random(DIMENSION, LEN);
+    let config = Config { ef_search: 60, ..Default::default() };
+    let collection = Collection::build(&config, &records).unwrap();
+    let query = Vector::random(DIMENSION);
+
+    // The pages within ef_search neither overlap nor skip records.
+    let mut pages: Vec<u32> = vec![];
+    for offset in (0..60).step_by(10) {
+        let page = collection.search_with_offset(&query, offset, 10).unwrap();
+        pages.extend(page.iter().map(|r| r.id));
+    }
+
+    let all = collection.search_with_offset(&query, 0, 60).unwrap();
+    let expected: Vec<u32> = all.iter().map(|r| r.id).collect();
+    assert_eq!(pages, expected);
+}
+
+#[test]
+fn recommend() {
+    let records = Record::many_random(DIMENSION, LEN);
+    let collection = create_collection(&records);
+    let positive = [VectorID(1), VectorID(2)];
+    let negative = [VectorID(3)];
+
+    for strategy in [RecommendStrategy::Average, RecommendStrategy::BestScore] {
+        let result = collection
+            .recommend_with_strategy(&positive, &negative, 10, strategy)
+            .unwrap();
+
+        // The examples are excluded from the recommendations.
+        assert_eq!(result.len(), 10);
+        assert!(result.iter().all(|r| ![1, 2, 3].contains(&r.id)));
+    }
+
+    // A single positive example recommends its nearest neighbors.
+    let query = &records[5].vector;
+    let expected = collection.search(query, 4).unwrap();
+    let result = collection.recommend(&[VectorID(5)], &[], 3).unwrap();
+    let ids: Vec<u32> = result.iter().map(|r| r.id).collect();
+    let expected_ids: Vec<u32> = expected[1..].iter().map(|r| r.id).collect();
+    assert_eq!(ids, expected_ids);
+
+    assert!(collection.recommend(&[], &negative, 10).is_err());
+    assert!(collection.recommend(&[VectorID(1000)], &[], 10).is_err());
+}
+
+#[test]
+fn update_deleted_neighbors() {
+    let records = Record::many_random(DIMENSION, LEN);
+    let mut collection = create_collection(&records);
+    collection.delete(&VectorID(0)).unwrap();
+    collection.delete(&VectorID(64)).unwrap();
+
+    // The vectors next to the deleted ones are kept as they are.
+    let record = Record::random(DIMENSION);
+    collection.update(&VectorID(63), &record).unwrap();
+    assert_eq!(collection.get(&VectorID(63)).unwrap().vector, record.vector);
+    assert_eq!(
+        collection.get(&VectorID(65)).unwrap().vector,
+        records[65].vector
+    );
+    assert!(collection.get(&VectorID(64)).is_err());
+
+    let result = collection.search(&records[1].vector, 1).unwrap();
+    assert_eq!(result[0].id, 1);
+    assert_eq!(result[0].distance, 0.0);
+}
+
+#[test]
+fn search_explain() {
+    let records = Record::many_random(DIMENSION, LEN);
+    let collection = create_collection(&records);
+
+    let (results, stats) =
+        collection.search_explain(&records[7].vector, 5).unwrap();
+    assert_eq!(results.len(), 5);
+    assert_eq!(results[0].id, 7);
+    assert_eq!(stats.ef, collection.config.ef_search);
+    assert!(stats.entry_point.is_some());
+    assert!(stats.distances >= stats.visited.iter().sum());
+    assert!(stats.total_time >= stats.search_time);
+
+    // The counters aggregate every search of the index.
+    collection.search(&records[8].vector, 5).unwrap();
+    let counters = collection.search_counters();
+    assert_eq!(counters.searches, 2);
+    assert!(counters.distances >= stats.distances as u64);
+
+    collection.reset_search_counters();
+    assert_eq!(collection.search_counters().searches, 0);
+}
+
+#[test]
+fn search_explain_flat() {
+    let records = Record::many_random(DIMENSION, LEN);
+    let config = Config { index: IndexType::Flat, ..Default::default() };
+    let collection = Collection::build(&config, &records).unwrap();
+
+    // The flat index computes the distance to every vector.
+    let (_, stats) = collection.search_explain(&records[0].vector, 5).unwrap();
+    assert_eq!(stats.distances, LEN);
+    assert!(stats.visited.is_empty());
+    assert!(stats.entry_point.is_none());
+}
+
+#[test]
+fn evaluate_recall() {
+    let records = Record::many_random(DIMENSION, LEN);
+    let config = Config { index: IndexType::Flat, ..Default::default() };
+    let collection = Collection::build(&config, &records).unwrap();
+
+    // The flat search finds all of the true nearest neighbors.
+    let queries = collection.sample_vectors(10);
+    assert_eq!(queries.len(), 10);
+    let report = collection.evaluate_recall(&queries, 10).unwrap();
+    assert_eq!(report.queries, 10);
+    assert_eq!(report.recall, 1.0);
+    assert!(report.p50 <= report.p95 && report.p95 <= report.p99);
+
+    assert!(collection.evaluate_recall(&[], 10).is_err());
+}
+
+#[test]
+fn tune_ef_search() {
+    let records = Record::many_random(DIMENSION, 1000);
+    let mut collection = create_collection(&records);
+
+    let report = collection.tune(0.95, 10, 50).unwrap();
+    assert!(report.recall >= 0.95);
+    assert!(report.ef_search >= 10);
+    assert_eq!(collection.config.ef_search, report.ef_search);
+
+    let values: Vec<usize> = report.curve.iter().map(|p| p.ef_search).collect();
+    assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(values.contains(&report.ef_search));
+
+    // The target must be a valid recall.
+    assert!(collection.tune(1.5, 10, 50).is_err());
+    assert_eq!(collection.config.ef_search, report.ef_search);
+}
+
+#[test]
+fn tune_unsupported_index() {
+    let records = Record::many_random(DIMENSION, LEN);
+    let config = Config { index: IndexType::Flat, ..Default::default() };
+    let mut collection = Collection::build(&config, &records).unwrap();
+    assert!(collection.tune(0.9, 10, 10).is_err());
+}
@@ -0,0 +1,238 @@
+use super::*;
+use std::sync::Arc;
+use std::thread;
+
+const DIMENSION: usize = 128;
+const LEN: usize = 100;
+
+fn create_concurrent_collection(records: &[Record]) -> ConcurrentCollection {
+    let config = Config::default();
+    ConcurrentCollection::build(&config, records).unwrap()
+}
+
+#[test]
+fn insert_and_get() {
+    let records = Record::many_random(DIMENSION, LEN);
+    let collection = create_concurrent_collection(&records);
+
+    let record = Record::random(DIMENSION);
+    let id = collection.insert(&record).unwrap();
+
+    assert_eq!(id, LEN.into());
+    assert_eq!(collection.len(), LEN + 1);
+    assert_eq!(collection.get(&id).unwrap().data, record.data);
+}
+
+#[test]
+fn insert_invalid_dimension() {
+    let records = Record::many_random(DIMENSION, LEN);
+    let collection = create_concurrent_collection(&records);
+
+    let record = Record::random(DIMENSION + 1);
+    assert!(collection.insert(&record).is_err());
+    assert_eq!(collection.len(), LEN);
+}
+
+#[test]
+fn delete_and_update() {
+    let records = Record::many_random(DIMENSION, LEN);
+    let collection = create_concurrent_collection(&records);
+
+    let id = VectorID(5);
+    let record = Record::random(DIMENSION);
+    collection.update(&id, &record).unwrap();
+    assert_eq!(collection.get(&id).unwrap().data, record.data);
+
+    collection.delete(&id).unwrap();
+    assert_eq!(collection.len(), LEN - 1);
+    assert!(collection.get(&id).is_err());
+    assert!(collection.delete(&id).is_err());
+}
+
+#[test]
+fn parallel_inserts() {
+    // A wider search keeps the exact match check from being flaky.
+    let config = Config { ef_search: 64, ..Default::default() };
+    let collection = Arc::new(ConcurrentCollection::new(&config));
+    let threads = 8;
+    let per_thread = 50;
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            let collection = collection.clone();
+            scope.spawn(move || {
+                for record in Record::many_random(DIMENSION, per_thread) {
+                    collection.insert(&record).unwrap();
+                }
+            });
+        }
+    });
+
+    assert_eq!(collection.len(), threads * per_thread);
+
+    // Every inserted record must be reachable by its own vector.
+    let record = collection.get(&VectorID(42)).unwrap();
+    let result = collection.search(&record.vector, 1).unwrap();
+    assert_eq!(result[0].distance, 0.0);
+}
+
+#[test]
+fn mixed_readers_and_writers() {
+    let records = Record::many_random(DIMENSION, 1000);
+    let collection = Arc::new(create_concurrent_collection(&records));
+
+    thread::scope(|scope| {
+        // Writers inserting, updating, and deleting records.
+        for i in 0..4 {
+            let collection = collection.clone();
+            scope.spawn(move || {
+                for j in 0..50 {
+                    let record = Record::random(DIMENSION);
+                    let id = collection.insert(&record).unwrap();
+
+                    let target = VectorID((i * 100 + j) as u32);
+                    match j % 3 {
+                        0 => collection.delete(&target).unwrap(),
+                        1 => collection.update(&id, &record).unwrap(),
+                        _ => {}
+                    }
+                }
+            });
+        }
+
+        // Readers searching while the writers are running.
+        for _ in 0..4 {
+            let collection = collection.clone();
+            scope.spawn(move || {
+                for _ in 0..100 {
+                    let query = Vector::random(DIMENSION);
+                    let result = collection.search(&query, 10).unwrap();
+                    assert_eq!(result.len(), 10);
+                    assert!(result
+                        .windows(2)
+                        .all(|w| { w[0].distance <= w[1].distance }));
+                }
+            });
+        }
+    });
+
+    // 200 inserted and 68 deleted records.
+    assert_eq!(collection.len(), 1000 + 200 - 68);
+}
+
+#[test]
+fn convert_to_collection() {
+    let records = Record::many_random(DIMENSION, LEN);
+    let concurrent = create_concurrent_collection(&records);
+    concurrent.insert(&Record::random(DIMENSION)).unwrap();
+
+    let collection = concurrent.to_collection();
+    assert_eq!(collection.len(), LEN + 1);
+    assert_eq!(collection.dimension(), DIMENSION);
+
+    let query = Vector::random(DIMENSION);
+    let expected = concurrent.search(&query, 5).unwrap();
+    let result = collection.search(&query, 5).unwrap();
+    assert_eq!(result[0].id, expected[0].id);
+
+    let concurrent: ConcurrentCollection = collection.into();
+    assert_eq!(concurrent.len(), LEN + 1);
+}
+
+#[test]
+fn save_and_load_collection() {
+    let mut db = Database::new("data/concurrent_save_and_load").unwrap();
+    let config =
+        Config { text_field: Some("title".into()), ..Default::default() };
+
+    let titles = ["Even text", "Odd text"];
+    let records: Vec<Record> = (0..10usize)
+        .map(|i| HashMap::from([("title", titles[i % 2])]).into())
+        .map(|data| Record::new(&Vector::random(DIMENSION), &data))
+        .collect();
+
+    let mut collection = Collection::build(&config, &records).unwrap();
+    collection.create_index("title", IndexKind::Hash).unwrap();
+    db.save_collection("vectors", &collection).unwrap();
+
+    // The indexes are kept up to date by the concurrent collection.
+    let collection = db.get_collection("vectors").unwrap();
+    let concurrent: ConcurrentCollection = collection.into();
+    let record = Record::new(
+        &Vector::random(DIMENSION),
+        &HashMap::from([("title", "Odd text")]).into(),
+    );
+
+    let id = concurrent.insert(&record).unwrap();
+    concurrent.delete(&VectorID(0)).unwrap();
+
+    let collection: Collection = concurrent.into();
+    db.save_collection("vectors", &collection).unwrap();
+
+    let collection = db.get_collection("vectors").unwrap();
+    let filter = Filter::equal("title", "Even text");
+    let ids = collection.find(&filter).unwrap();
+    assert_eq!(ids, vec![VectorID(2), VectorID(4), VectorID(6), VectorID(8)]);
+    assert_eq!(collection.text_search("odd", 10).unwrap().len(), 6);
+    assert_eq!(collection.search(&record.vector, 1).unwrap()[0].id, id.0);
+}
+
+#[test]
+fn parallel_inserts_recall() {
+    let config = Config::default();
+    let collection = Arc::new(ConcurrentCollection::new(&config));
+    let records = Record::many_random(DIMENSION, 2000);
+
+    thread::scope(|scope| {
+        for chunk in records.chunks(250) {
+            let collection = collection.clone();
+            scope.spawn(move || {
+                for record in chunk {
+                    collection.insert(record).unwrap();
+                }
+            });
+        }
+    });
+
+    // The upper layers are rebuilt as the collection grows so the
+    // recall is close to the recall of a collection built at once.
+    let queries: Vec<Vector> =
+        (0..50).map(|_| Vector::random(DIMENSION)).collect();
+    let built = Collection::build(&config, &records).unwrap();
+    let expected = built.evaluate_recall(&queries, 10).unwrap().recall;
+    let collection = collection.to_collection();
+    let report = collection.evaluate_recall(&queries, 10).unwrap();
+    assert!(report.recall >= expected - 0.05);
+}
+
+#[test]
+fn wal_checkpoint() {
+    let mut db = Database::new("data/concurrent_wal_checkpoint").unwrap();
+    let records = Record::many_random(DIMENSION, LEN);
+    let collection = Collection::build(&Config::default(), &records).unwrap();
+    db.save_collection("vectors", &collection).unwrap();
+    db.enable_wal("vectors", 10).unwrap();
+
+    let collection = db.get_collection("vectors").unwrap();
+    let concurrent = Arc::new(ConcurrentCollection::from(collection));
+    thread::scope(|scope| {
+        for _ in 0..5 {
+            let concurrent = concurrent.clone();
+            scope.spawn(move || {
+                for record in Record::many_random(DIMENSION, 5) {
+                    concurrent.insert(&record).unwrap();
+                }
+            });
+        }
+    });
+
+    // The log is checkpointed after every 10 operations. Other
+    // threads might log more operations before the checkpoint.
+    let info = db.collection_info("vectors").unwrap();
+    assert!(info.len >= LEN + 20);
+    assert_eq!(concurrent.get(&VectorID(0)).unwrap().data, records[0].data);
+
+    let collection = db.get_collection("vectors").unwrap();
+    assert_eq!(collection.len(), LEN + 25);
+    assert_eq!(collection.search(&records[0].vector, 1).unwrap()[0].id, 0);
+}
@@ -58,3 +58,588 @@ fn delete_collection() {
     db.delete_collection("vectors").unwrap();
     assert_eq!(db.len(), 0);
 }
+
+#[test]
+fn wal_replay_after_reopen() {
+    let path = "data/wal_replay_after_reopen";
+    let record = Record::random(128);
+
+    {
+        let mut db = create_test_database(path);
+        db.enable_wal("vectors", 0).unwrap();
+
+        // Changes are not saved to the collection explicitly.
+        let mut collection = db.get_collection("vectors").unwrap();
+        collection.insert(&record).unwrap();
+        collection.insert(&Record::random(128)).unwrap();
+        collection.delete(&VectorID(0)).unwrap();
+        collection.update_data(&VectorID(1), &"updated".into()).unwrap();
+    }
+
+    let db = Database::open(path).unwrap();
+    let collection = db.get_collection("vectors").unwrap();
+    assert_eq!(collection.len(), 101);
+    assert_eq!(collection.get(&VectorID(100)).unwrap().data, record.data);
+    assert_eq!(collection.get(&VectorID(1)).unwrap().data, "updated".into());
+    assert!(!collection.contains(&VectorID(0)));
+}
+
+#[test]
+fn wal_failed_operations() {
+    let path = "data/wal_failed_operations";
+    let mut db = create_test_database(path);
+    db.enable_wal("vectors", 0).unwrap();
+
+    // The failed operations aren't logged to fail again on replay.
+    let mut collection = db.get_collection("vectors").unwrap();
+    let mut vector = Vector::random(128);
+    vector.0[0] = f32::NAN;
+    let record = Record::new(&vector, &0usize.into());
+    assert!(collection.insert(&record).is_err());
+    collection.delete(&VectorID(3)).unwrap();
+    assert!(collection.delete(&VectorID(3)).is_err());
+    assert!(collection.update_data(&VectorID(3), &"updated".into()).is_err());
+
+    let collection = db.get_collection("vectors").unwrap();
+    assert_eq!(collection.len(), 99);
+}
+
+#[test]
+fn wal_checkpoint() {
+    let path = "data/wal_checkpoint";
+
+    {
+        let mut db = create_test_database(path);
+        db.enable_wal("vectors", 2).unwrap();
+
+        let mut collection = db.get_collection("vectors").unwrap();
+        for record in Record::many_random(128, 3) {
+            collection.insert(&record).unwrap();
+        }
+    }
+
+    let mut db = Database::open(path).unwrap();
+    let collection = db.get_collection("vectors").unwrap();
+    assert_eq!(collection.len(), 103);
+
+    // Disabling the log keeps the pending operations.
+    db.disable_wal("vectors").unwrap();
+    let collection = db.get_collection("vectors").unwrap();
+    assert_eq!(collection.len(), 103);
+}
+
+#[test]
+fn wal_save_collection() {
+    let mut db = create_test_database("data/wal_save_collection");
+    db.enable_wal("vectors", 0).unwrap();
+
+    let mut collection = db.get_collection("vectors").unwrap();
+    collection.insert(&Record::random(128)).unwrap();
+    db.save_collection("vectors", &collection).unwrap();
+
+    // The saved collection must not replay the logged insert.
+    let collection = db.get_collection("vectors").unwrap();
+    assert_eq!(collection.len(), 101);
+}
+
+#[test]
+fn wal_single_owner() {
+    let mut db = create_test_database("data/wal_single_owner");
+    db.enable_wal("vectors", 0).unwrap();
+
+    let mut owner = db.get_collection("vectors").unwrap();
+    owner.insert(&Record::random(128)).unwrap();
+
+    // Another handle can read but not log the operations.
+    let mut other = db.get_collection("vectors").unwrap();
+    assert_eq!(other.len(), 101);
+    assert!(other.insert(&Record::random(128)).is_err());
+    assert!(other.delete(&VectorID(0)).is_err());
+    assert!(db.save_collection("vectors", &other).is_err());
+    assert_eq!(other.len(), 101);
+
+    // The log is released with the owner.
+    drop(owner);
+    other.insert(&Record::random(128)).unwrap();
+    drop(other);
+
+    let collection = db.get_collection("vectors").unwrap();
+    assert_eq!(collection.len(), 102);
+}
+
+#[test]
+fn wal_collection_not_found() {
+    let mut db = Database::new("data/wal_collection_not_found").unwrap();
+    assert!(db.enable_wal("vectors", 0).is_err());
+}
+
+#[test]
+fn disk_collection_search() {
+    let mut db = Database::new("data/disk_collection_search").unwrap();
+
+    let records = Record::many_random(128, 1000);
+    let mut collection = super::create_collection(&records);
+    collection.delete(&VectorID(3)).unwrap();
+    db.save_disk_collection("disk", &collection).unwrap();
+
+    let disk = db.get_disk_collection("disk").unwrap();
+    assert_eq!(disk.len(), 999);
+    assert_eq!(disk.dimension(), 128);
+    assert!(!disk.contains(&VectorID(3)));
+
+    // The disk search must traverse the same graph.
+    let query = Vector::random(128);
+    let expected = collection.search(&query, 5).unwrap();
+    let result = disk.search(&query, 5).unwrap();
+
+    let ids: Vec<u32> = result.iter().map(|r| r.id).collect();
+    let expected_ids: Vec<u32> = expected.iter().map(|r| r.id).collect();
+    assert_eq!(ids, expected_ids);
+    assert_eq!(result[0].data, expected[0].data);
+
+    let record = disk.get(&VectorID(5)).unwrap();
+    assert_eq!(record.vector, records[5].vector);
+    assert_eq!(record.data, records[5].data);
+}
+
+#[test]
+fn disk_collection_after_update() {
+    let mut db = Database::new("data/disk_collection_after_update").unwrap();
+
+    let records = Record::many_random(128, 100);
+    let mut collection = super::create_collection(&records);
+    let record = Record::random(128);
+    collection.update(&VectorID(5), &record).unwrap();
+    collection.insert(&Record::random(128)).unwrap();
+    db.save_disk_collection("disk", &collection).unwrap();
+
+    let disk = db.get_disk_collection("disk").unwrap();
+    assert_eq!(disk.len(), 101);
+    assert_eq!(disk.get(&VectorID(5)).unwrap().vector, record.vector);
+    let result = disk.search(&record.vector, 1).unwrap();
+    assert_eq!(result[0].id, 5);
+}
+
+#[test]
+fn disk_collection_save_over_open() {
+    let mut db = Database::new("data/disk_collection_save_over_open").unwrap();
+    let records = Record::many_random(128, 100);
+    let collection = super::create_collection(&records);
+    db.save_disk_collection("disk", &collection).unwrap();
+    let disk = db.get_disk_collection("disk").unwrap();
+
+    // The smaller file replaces the file the open collection maps.
+    let other = super::create_collection(&Record::many_random(128, 50));
+    db.save_disk_collection("disk", &other).unwrap();
+
+    let result = disk.search_ids(&records[90].vector, 1).unwrap();
+    assert_eq!(result[0], (VectorID(90), 0.0));
+    let result = disk.search(&records[90].vector, 1).unwrap();
+    assert_eq!(result[0].data, records[90].data);
+    assert_eq!(db.get_disk_collection("disk").unwrap().len(), 50);
+}
+
+#[test]
+fn delete_disk_collection() {
+    let mut db = Database::new("data/delete_disk_collection").unwrap();
+    let collection = super::create_collection(&Record::many_random(128, 100));
+    db.save_disk_collection("disk", &collection).unwrap();
+
+    db.delete_disk_collection("disk").unwrap();
+    assert!(db.get_disk_collection("disk").is_err());
+    assert!(db.delete_disk_collection("disk").is_err());
+}
+
+#[test]
+fn disk_collection_normalize() {
+    let mut db = Database::new("data/disk_collection_normalize").unwrap();
+    let config = Config { normalize: true, ..Default::default() };
+    let records = Record::many_random(128, 100);
+    let collection = Collection::build(&config, &records).unwrap();
+    db.save_disk_collection("disk", &collection).unwrap();
+
+    // The query is normalized like the stored vectors.
+    let disk = db.get_disk_collection("disk").unwrap();
+    let query = records[9].vector.0.iter().map(|x| x * 2.0).collect::<Vec<_>>();
+    let result = disk.search(&query.into(), 1).unwrap();
+    assert_eq!(result[0].id, 9);
+}
+
+#[test]
+fn lazy_metadata() {
+    let mut db = Database::new("data/lazy_metadata").unwrap();
+    let records = Record::many_random(128, 100);
+    db.create_collection("vectors", None, Some(&records)).unwrap();
+
+    // The metadata is loaded from the tree on demand.
+    let mut collection = db.get_collection("vectors").unwrap();
+    assert_eq!(collection.get(&VectorID(7)).unwrap().data, records[7].data);
+
+    let record = Record::random(128);
+    collection.update(&VectorID(7), &record).unwrap();
+    collection.delete(&VectorID(8)).unwrap();
+    db.save_collection("vectors", &collection).unwrap();
+
+    // The changes are visible before and after reloading.
+    for collection in [collection, db.get_collection("vectors").unwrap()] {
+        let data = collection.get(&VectorID(7)).unwrap().data;
+        assert_eq!(data, record.data);
+        assert!(collection.get(&VectorID(8)).is_err());
+    }
+}
+
+#[test]
+fn save_collection_copy_metadata() {
+    let mut db = create_test_database("data/save_collection_copy_metadata");
+    let collection = db.get_collection("vectors").unwrap();
+    let expected = collection.get(&VectorID(3)).unwrap();
+
+    // The metadata is copied from the tree of the source collection.
+    db.save_collection("copy", &collection).unwrap();
+    let copy = db.get_collection("copy").unwrap();
+    assert_eq!(copy.get(&VectorID(3)).unwrap().data, expected.data);
+    assert_eq!(copy.len(), 100);
+}
+
+#[test]
+fn save_collection_other_database() {
+    let db = create_test_database("data/save_collection_other_database");
+    let mut collection = db.get_collection("vectors").unwrap();
+    let expected = collection.get(&VectorID(3)).unwrap();
+    collection.update(&VectorID(5), &Record::random(128)).unwrap();
+
+    // The tree with the same name in the other database is empty
+    // so all of the metadata is written to it.
+    let mut other = Database::new("data/save_collection_other_db").unwrap();
+    other.save_collection("vectors", &collection).unwrap();
+    let copy = other.get_collection("vectors").unwrap();
+    assert_eq!(copy.get(&VectorID(3)).unwrap().data, expected.data);
+    assert_eq!(copy.records().unwrap().len(), 100);
+}
+
+#[test]
+fn delete_collection_loaded() {
+    let mut db = create_test_database("data/delete_collection_loaded");
+    let mut collection = db.get_collection("vectors").unwrap();
+    let record = Record::random(128);
+    collection.update(&VectorID(5), &record).unwrap();
+
+    // The loaded collection keeps the metadata of the dropped tree.
+    let expected = db.get_collection("vectors").unwrap().get(&VectorID(3));
+    db.delete_collection("vectors").unwrap();
+    let data = collection.get(&VectorID(3)).unwrap().data;
+    assert_eq!(data, expected.unwrap().data);
+    assert_eq!(collection.get(&VectorID(5)).unwrap().data, record.data);
+
+    let result = collection.search(&Vector::random(128), 10).unwrap();
+    assert_eq!(result.len(), 10);
+    assert_eq!(collection.records().unwrap().len(), 100);
+}
+
+#[test]
+fn rename_collection_loaded() {
+    let mut db = create_test_database("data/rename_collection_loaded");
+    let collection = db.get_collection("vectors").unwrap();
+    let expected = collection.search(&Vector::random(128), 10).unwrap();
+    let query = &expected[0];
+
+    db.rename_collection("vectors", "renamed").unwrap();
+    let vector = collection.get(&VectorID(query.id)).unwrap().vector;
+    let result = collection.search(&vector, 1).unwrap();
+    assert_eq!(result[0].id, query.id);
+    assert_eq!(result[0].data, query.data);
+}
+
+#[test]
+fn save_collection_loaded() {
+    let mut db = create_test_database("data/save_collection_loaded");
+    let collection = db.get_collection("vectors").unwrap();
+    let expected = collection.get(&VectorID(3)).unwrap().data;
+
+    // Saving another handle doesn't change the metadata of this one.
+    let mut other = db.get_collection("vectors").unwrap();
+    other.update(&VectorID(3), &Record::random(128)).unwrap();
+    other.delete(&VectorID(4)).unwrap();
+    db.save_collection("vectors", &other).unwrap();
+
+    assert_eq!(collection.get(&VectorID(3)).unwrap().data, expected);
+    assert!(collection.get(&VectorID(4)).is_ok());
+    assert_eq!(collection.search(&Vector::random(128), 10).unwrap().len(), 10);
+
+    // The saved handle and the new handles read the saved metadata.
+    let data = other.get(&VectorID(3)).unwrap().data;
+    assert_ne!(data, expected);
+    let loaded = db.get_collection("vectors").unwrap();
+    assert_eq!(loaded.get(&VectorID(3)).unwrap().data, data);
+    assert!(loaded.get(&VectorID(4)).is_err());
+
+    // The detached handle writes all of its metadata when it's saved.
+    db.save_collection("vectors", &collection).unwrap();
+    let loaded = db.get_collection("vectors").unwrap();
+    assert_eq!(loaded.get(&VectorID(3)).unwrap().data, expected);
+    assert!(loaded.get(&VectorID(4)).is_ok());
+    assert_eq!(other.get(&VectorID(3)).unwrap().data, data);
+}
+
+#[test]
+fn delete_collection_not_found() {
+    let mut db = create_test_database("data/delete_collection_not_found");
+    assert!(db.delete_collection("unknown").is_err());
+    assert_eq!(db.len(), 1);
+}
+
+#[test]
+fn list_collections() {
+    let mut db = create_test_database("data/list_collections");
+    db.save_collection("other", &Collection::new(&Config::default())).unwrap();
+
+    let mut names = db.list_collections().unwrap();
+    names.sort();
+    assert_eq!(names, vec!["other", "vectors"]);
+}
+
+#[test]
+fn collection_info() {
+    let db = create_test_database("data/collection_info");
+    let info = db.collection_info("vectors").unwrap();
+
+    assert_eq!(info.len, 100);
+    assert_eq!(info.dimension, 128);
+    assert_eq!(info.config.ef_search, Config::default().ef_search);
+    assert!(db.collection_info("unknown").is_err());
+}
+
+#[test]
+fn copy_collection() {
+    let mut db = create_test_database("data/copy_collection");
+    db.copy_collection("vectors", "copy").unwrap();
+    assert_eq!(db.len(), 2);
+
+    // Changes to the copy don't affect the source.
+    let mut copy = db.get_collection("copy").unwrap();
+    copy.delete(&VectorID(0)).unwrap();
+    db.save_collection("copy", &copy).unwrap();
+
+    let source = db.get_collection("vectors").unwrap();
+    assert_eq!(source.len(), 100);
+    assert!(source.get(&VectorID(0)).is_ok());
+    assert_eq!(db.collection_info("copy").unwrap().len, 99);
+
+    assert!(db.copy_collection("vectors", "copy").is_err());
+    assert!(db.copy_collection("unknown", "new").is_err());
+}
+
+#[test]
+fn rename_collection() {
+    let mut db = create_test_database("data/rename_collection");
+    db.enable_wal("vectors", 0).unwrap();
+
+    // The pending logged operations are moved with the collection.
+    let mut collection = db.get_collection("vectors").unwrap();
+    collection.insert(&Record::random(128)).unwrap();
+
+    db.rename_collection("vectors", "renamed").unwrap();
+    assert_eq!(db.len(), 1);
+    assert!(db.get_collection("vectors").is_err());
+
+    let collection = db.get_collection("renamed").unwrap();
+    assert_eq!(collection.len(), 101);
+    assert!(collection.get(&VectorID(5)).is_ok());
+}
+
+#[test]
+fn persist_metadata_indexes() {
+    let mut db = Database::new("data/persist_metadata_indexes").unwrap();
+    let records: Vec<Record> = (0..10usize)
+        .map(|i| HashMap::from([("group", i % 3)]).into())
+        .map(|data| Record::new(&Vector::random(128), &data))
+        .collect();
+
+    let mut collection =
+        Collection::build(&Config::default(), &records).unwrap();
+    collection.create_index("group", IndexKind::Hash).unwrap();
+    db.save_collection("vectors", &collection).unwrap();
+    db.enable_wal("vectors", 0).unwrap();
+
+    // The logged operations update the loaded indexes on replay.
+    let mut collection = db.get_collection("vectors").unwrap();
+    collection.delete(&VectorID(0)).unwrap();
+
+    let collection = db.get_collection("vectors").unwrap();
+    let ids = collection.find(&Filter::equal("group", 0usize)).unwrap();
+    assert_eq!(ids, vec![VectorID(3), VectorID(6), VectorID(9)]);
+
+    // The indexes are copied and removed with the collection.
+    db.copy_collection("vectors", "copy").unwrap();
+    db.delete_collection("vectors").unwrap();
+    let copy = db.get_collection("copy").unwrap();
+    assert_eq!(
+        copy.list_indexes(),
+        vec![("group".to_string(), IndexKind::Hash)]
+    );
+}
+
+#[test]
+fn persist_schema() {
+    let mut db = Database::new("data/persist_schema").unwrap();
+    let schema = Schema::new().required("title", FieldType::Text);
+    let config = Config { schema: Some(schema.clone()), ..Default::default() };
+    db.create_collection("vectors", Some(&config), None).unwrap();
+
+    let mut collection = db.get_collection("vectors").unwrap();
+    assert_eq!(collection.config.schema, Some(schema.clone()));
+    assert!(collection.insert(&Record::random(128)).is_err());
+
+    let info = db.collection_info("vectors").unwrap();
+    assert_eq!(info.config.schema, Some(schema));
+}
+
+#[test]
+fn persist_text_index() {
+    let mut db = Database::new("data/persist_text_index").unwrap();
+    let config =
+        Config { text_field: Some("title".into()), ..Default::default() };
+    db.create_collection("vectors", Some(&config), None).unwrap();
+    db.enable_wal("vectors", 0).unwrap();
+
+    let mut collection = db.get_collection("vectors").unwrap();
+    let data = HashMap::from([("title", "Stored text")]).into();
+    collection.insert(&Record::new(&Vector::random(128), &data)).unwrap();
+    db.save_collection("vectors", &collection).unwrap();
+
+    // The logged operations update the loaded text index on replay.
+    let data = HashMap::from([("title", "Logged text")]).into();
+    collection.insert(&Record::new(&Vector::random(128), &data)).unwrap();
+
+    let collection = db.get_collection("vectors").unwrap();
+    let result = collection.text_search("text", 10).unwrap();
+    assert_eq!(result.len(), 2);
+    assert_eq!(collection.text_search("logged", 10).unwrap()[0].id, 1);
+
+    // The text index is copied with the collection.
+    db.copy_collection("vectors", "copy").unwrap();
+    let copy = db.get_collection("copy").unwrap();
+    assert_eq!(copy.text_search("stored", 10).unwrap()[0].id, 0);
+}
+
+#[test]
+fn persist_sparse_vectors() {
+    let mut db = create_test_database("data/persist_sparse_vectors");
+    db.enable_wal("vectors", 0).unwrap();
+
+    let mut collection = db.get_collection("vectors").unwrap();
+    let sparse = SparseVector::new(&[(2, 1.0), (8, 0.5)]);
+    collection.insert(&Record::random(128).with_sparse(&sparse)).unwrap();
+    db.save_collection("vectors", &collection).unwrap();
+
+    // The logged sparse vectors are restored on replay.
+    let logged = SparseVector::new(&[(8, 2.0)]);
+    collection.insert(&Record::random(128).with_sparse(&logged)).unwrap();
+
+    let collection = db.get_collection("vectors").unwrap();
+    let record = collection.get(&VectorID(100)).unwrap();
+    assert_eq!(record.sparse, Some(sparse));
+
+    let result = collection.sparse_search(&logged, 10).unwrap();
+    let ids: Vec<u32> = result.iter().map(|r| r.id).collect();
+    assert_eq!(ids, vec![101, 100]);
+}
+
+#[test]
+fn persist_vector_fields() {
+    let mut db = Database::new("data/persist_vector_fields").unwrap();
+    let config =
+        Config { vector_fields: vec!["title".into()], ..Default::default() };
+    let title = Vector::random(32);
+    let record = Record::random(128).with_vector("title", &title);
+    db.create_collection("vectors", Some(&config), Some(&[record])).unwrap();
+    db.enable_wal("vectors", 0).unwrap();
+
+    // The logged field vectors are restored on replay.
+    let logged = Vector::random(32);
+    let mut collection = db.get_collection("vectors").unwrap();
+    let record = Record::random(128).with_vector("title", &logged);
+    collection.insert(&record).unwrap();
+
+    let collection = db.get_collection("vectors").unwrap();
+    let record = collection.get(&VectorID(0)).unwrap();
+    assert_eq!(record.vectors["title"], vec![title]);
+    let result = collection.search_field("title", &logged, 1).unwrap();
+    assert_eq!(result[0].id, 1);
+}
+
+#[test]
+fn persist_flat_index() {
+    let mut db = Database::new("data/persist_flat_index").unwrap();
+    let config = Config { index: IndexType::Flat, ..Default::default() };
+    let records = Record::many_random(128, 100);
+    db.create_collection("vectors", Some(&config), Some(&records)).unwrap();
+    db.enable_wal("vectors", 0).unwrap();
+
+    // The logged vectors are added to the rebuilt flat index.
+    let record = Record::random(128);
+    let mut collection = db.get_collection("vectors").unwrap();
+    collection.insert(&record).unwrap();
+
+    let collection = db.get_collection("vectors").unwrap();
+    assert_eq!(collection.config.index, IndexType::Flat);
+    let result = collection.search(&record.vector, 1).unwrap();
+    assert_eq!(result[0].id, 100);
+
+    // The disk collection only supports the HNSW index.
+    assert!(db.save_disk_collection("disk", &collection).is_err());
+}
+
+#[test]
+fn persist_ivf_index() {
+    let mut db = Database::new("data/persist_ivf_index").unwrap();
+    let index = IndexType::Ivf { lists: 8, probes: 8 };
+    let config = Config { index, ..Default::default() };
+    let records = Record::many_random(128, 100);
+    db.create_collection("vectors", Some(&config), Some(&records)).unwrap();
+    db.enable_wal("vectors", 0).unwrap();
+
+    // The logged vectors are added to the stored lists.
+    let record = Record::random(128);
+    let mut collection = db.get_collection("vectors").unwrap();
+    collection.insert(&record).unwrap();
+
+    let collection = db.get_collection("vectors").unwrap();
+    let result = collection.search(&record.vector, 1).unwrap();
+    assert_eq!(result[0].id, 100);
+    let result = collection.search(&records[9].vector, 1).unwrap();
+    assert_eq!(result[0].id, 9);
+
+    // The IVF index is removed with the collection.
+    db.delete_collection("vectors").unwrap();
+    assert!(db.get_collection("vectors").is_err());
+}
+
+#[test]
+fn migrate_legacy_vectors() {
+    let path = "data/migrate_legacy_vectors";
+    let records = Record::many_random(128, 100);
+    let collection = Collection::build(&Config::default(), &records).unwrap();
+    let mut db = Database::new(path).unwrap();
+    db.save_collection("vectors", &collection).unwrap();
+    drop(db);
+
+    // Store the collection in the format before the vector arena
+    // which keeps the vectors in the collection itself.
+    let tree = sled::open(path).unwrap();
+    let value = bincode::serialize(&collection).unwrap();
+    tree.insert("vectors", value).unwrap();
+    tree.open_tree("vectors").unwrap().remove("vectors").unwrap();
+    drop(tree);
+
+    let mut db = Database::open(path).unwrap();
+    let collection = db.get_collection("vectors").unwrap();
+    assert_eq!(collection.get(&VectorID(9)).unwrap().vector, records[9].vector);
+
+    // The vectors are moved to the arena on the next save.
+    db.save_collection("vectors", &collection).unwrap();
+    let collection = db.get_collection("vectors").unwrap();
+    let result = collection.search(&records[9].vector, 1).unwrap();
+    assert_eq!(result[0].id, 9);
+    assert_eq!(collection.len(), 100);
+}
@@ -0,0 +1,106 @@
+use super::*;
+
+fn create_field_collection(len: usize) -> Collection {
+    let records: Vec<Record> = (0..len)
+        .map(|_| {
+            let tokens: Vec<Vector> =
+                (0..3).map(|_| Vector::random(16)).collect();
+            Record::random(128)
+                .with_vectors("tokens", &tokens)
+                .with_vector("title", &Vector::random(32))
+        })
+        .collect();
+
+    let vector_fields = vec!["title".into(), "tokens".into()];
+    let config = Config { vector_fields, ..Default::default() };
+    Collection::build(&config, &records).unwrap()
+}
+
+#[test]
+fn search_field() {
+    let collection = create_field_collection(100);
+    let record = collection.get(&VectorID(7)).unwrap();
+    assert_eq!(record.vectors["tokens"].len(), 3);
+
+    let result =
+        collection.search_field("title", &record.vectors["title"][0], 5);
+    let result = result.unwrap();
+    assert_eq!(result.len(), 5);
+    assert_eq!(result[0].id, 7);
+    assert_eq!(result[0].distance, 0.0);
+    assert_eq!(result[0].data, record.data);
+
+    // Any of the vectors in a field matches the record.
+    let query = &record.vectors["tokens"][2];
+    let result = collection.search_field("tokens", query, 1).unwrap();
+    assert_eq!(result[0].id, 7);
+}
+
+#[test]
+fn search_fields_aggregation() {
+    let collection = create_field_collection(100);
+    let record = collection.get(&VectorID(3)).unwrap();
+
+    // Late interaction with all of the token vectors.
+    let queries =
+        HashMap::from([("tokens".into(), record.vectors["tokens"].clone())]);
+    let result = collection.search_fields(&queries, 3, Aggregation::Sum);
+    let result = result.unwrap();
+    assert_eq!(result[0].id, 3);
+    assert_eq!(result[0].distance, 0.0);
+    assert!(result[1].distance > 0.0);
+
+    // The title matches exactly but the random token doesn't.
+    let queries = HashMap::from([
+        ("title".into(), record.vectors["title"].clone()),
+        ("tokens".into(), vec![Vector::random(16)]),
+    ]);
+
+    let result = collection.search_fields(&queries, 1, Aggregation::MaxSim);
+    assert_eq!(result.unwrap()[0].distance, 0.0);
+    let result = collection.search_fields(&queries, 1, Aggregation::Sum);
+    assert!(result.unwrap()[0].distance > 0.0);
+}
+
+#[test]
+fn field_incremental() {
+    let mut collection = create_field_collection(10);
+    let title = Vector::random(32);
+
+    let record = Record::random(128).with_vector("title", &title);
+    collection.insert(&record).unwrap();
+    collection.update(&VectorID(4), &Record::random(128)).unwrap();
+    collection.delete(&VectorID(5)).unwrap();
+
+    let result = collection.search_field("title", &title, 20).unwrap();
+    assert_eq!(result[0].id, 10);
+    assert_eq!(result.len(), 9);
+    assert!(collection.get(&VectorID(4)).unwrap().vectors.is_empty());
+}
+
+#[test]
+fn field_invalid() {
+    let mut collection = create_field_collection(10);
+
+    let record =
+        Record::random(128).with_vector("unknown", &Vector::random(32));
+    assert!(collection.insert(&record).is_err());
+    let record = Record::random(128).with_vector("title", &Vector::random(8));
+    assert!(collection.insert(&record).is_err());
+    assert!(collection
+        .search_field("unknown", &Vector::random(32), 5)
+        .is_err());
+    assert_eq!(collection.len(), 10);
+}
+
+#[test]
+fn concurrent_field() {
+    let collection: ConcurrentCollection = create_field_collection(10).into();
+    let title = Vector::random(32);
+    let record = Record::random(128).with_vector("title", &title);
+    collection.insert(&record).unwrap();
+
+    let collection = collection.to_collection();
+    let result = collection.search_field("title", &title, 1).unwrap();
+    assert_eq!(result[0].id, 10);
+}
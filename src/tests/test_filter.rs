@@ -0,0 +1,118 @@
+use super::*;
+use crate::metadata::*;
+use std::ops::Bound;
+
+const DIMENSION: usize = 32;
+
+fn create_article(source: &str, year: i64, tags: Vec<&str>) -> Record {
+    let tags = Metadata::from(tags);
+    let data = HashMap::from([
+        ("source", Metadata::from(source)),
+        ("year", Metadata::from(year)),
+        ("tags", tags),
+    ]);
+
+    Record::new(&Vector::random(DIMENSION), &data.into())
+}
+
+fn create_indexed_collection() -> Collection {
+    let records = vec![
+        create_article("wiki", 2001, vec!["rust"]),
+        create_article("blog", 2005, vec!["rust", "hnsw"]),
+        create_article("wiki", 2010, vec!["python"]),
+        create_article("news", 2015, vec![]),
+    ];
+
+    let mut collection = create_collection(&records);
+    collection.create_index("source", IndexKind::Hash).unwrap();
+    collection.create_index("year", IndexKind::BTree).unwrap();
+    collection.create_index("tags", IndexKind::Hash).unwrap();
+    collection
+}
+
+#[test]
+fn find_equal() {
+    let collection = create_indexed_collection();
+
+    let ids = collection.find(&Filter::equal("source", "wiki")).unwrap();
+    assert_eq!(ids, vec![VectorID(0), VectorID(2)]);
+
+    // Arrays match if any of their items is equal.
+    let ids = collection.find(&Filter::equal("tags", "rust")).unwrap();
+    assert_eq!(ids, vec![VectorID(0), VectorID(1)]);
+
+    // Numbers of different types are equal by their value.
+    let ids = collection.find(&Filter::equal("year", 2005usize)).unwrap();
+    assert_eq!(ids, vec![VectorID(1)]);
+    let ids = collection.find(&Filter::equal("year", 2005.0)).unwrap();
+    assert_eq!(ids, vec![VectorID(1)]);
+}
+
+#[test]
+fn find_range() {
+    let collection = create_indexed_collection();
+
+    let ids = collection.find(&Filter::range("year", 2005..2015)).unwrap();
+    assert_eq!(ids, vec![VectorID(1), VectorID(2)]);
+
+    let ids = collection.find(&Filter::range("year", 2004.5..)).unwrap();
+    assert_eq!(ids, vec![VectorID(1), VectorID(2), VectorID(3)]);
+
+    let (start, end) =
+        (Bound::Included(2010.into()), Bound::Excluded(2005.into()));
+    let filter = Filter::Range("year".into(), start, end);
+    assert!(collection.find(&filter).unwrap().is_empty());
+
+    // Range queries require a B-tree index.
+    let filter = Filter::range("source", "a"..="z");
+    assert!(collection.find(&filter).is_err());
+}
+
+#[test]
+fn find_combined() {
+    let collection = create_indexed_collection();
+
+    let filter = Filter::And(vec![
+        Filter::equal("source", "wiki"),
+        Filter::range("year", ..2005),
+    ]);
+
+    assert_eq!(collection.find(&filter).unwrap(), vec![VectorID(0)]);
+
+    let filter = Filter::Or(vec![
+        Filter::equal("source", "news"),
+        Filter::equal("tags", "hnsw"),
+    ]);
+
+    let ids = collection.find(&filter).unwrap();
+    assert_eq!(ids, vec![VectorID(1), VectorID(3)]);
+}
+
+#[test]
+fn find_not_indexed() {
+    let mut collection = create_indexed_collection();
+    assert!(collection.find(&Filter::equal("author", "x")).is_err());
+
+    collection.drop_index("source").unwrap();
+    assert!(collection.find(&Filter::equal("source", "wiki")).is_err());
+    assert!(collection.drop_index("source").is_err());
+    assert!(collection.create_index("year", IndexKind::Hash).is_err());
+    assert_eq!(collection.list_indexes().len(), 2);
+}
+
+#[test]
+fn index_maintenance() {
+    let mut collection = create_indexed_collection();
+    let filter = Filter::equal("source", "wiki");
+
+    collection.insert(&create_article("wiki", 2020, vec![])).unwrap();
+    collection.delete(&VectorID(0)).unwrap();
+
+    let record = create_article("blog", 2002, vec![]);
+    collection.update(&VectorID(2), &record).unwrap();
+
+    assert_eq!(collection.find(&filter).unwrap(), vec![VectorID(4)]);
+
+    let ids = collection.find(&Filter::range("year", ..=2002)).unwrap();
+    assert_eq!(ids, vec![VectorID(2)]);
+}
@@ -0,0 +1,177 @@
+use super::*;
+use crate::metadata::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[test]
+fn path_lookup() {
+    let names = Metadata::from(vec!["Elijah", "Samson"]);
+    let author = HashMap::from([("names", names), ("age", 30.into())]);
+    let data = Metadata::from(HashMap::from([("author", author)]));
+
+    let name = data.get("author.names.1").unwrap();
+    assert_eq!(name.as_str(), Some("Samson"));
+    assert_eq!(data.get("author.age").unwrap().as_i64(), Some(30));
+
+    assert!(data.get("author.names.2").is_none());
+    assert!(data.get("author.age.value").is_none());
+}
+
+#[test]
+fn primitive_conversions() {
+    assert_eq!(Metadata::from(true).as_bool(), Some(true));
+    assert_eq!(Metadata::from(-5i64), Metadata::Signed(-5));
+    assert_eq!(Metadata::from(None::<bool>), Metadata::Null);
+    assert!(Metadata::Null.is_null());
+
+    let bytes: &[u8] = &[1, 2, 3];
+    assert_eq!(Metadata::from(bytes).as_bytes(), Some(bytes));
+
+    let value: f64 = Metadata::from(0.1f64).try_into().unwrap();
+    assert_eq!(value, 0.1);
+
+    let value: usize = Metadata::Signed(7).try_into().unwrap();
+    assert_eq!(value, 7);
+
+    assert!(usize::try_from(Metadata::Signed(-1)).is_err());
+    assert!(String::try_from(Metadata::Boolean(false)).is_err());
+}
+
+#[test]
+fn timestamp_conversion() {
+    let time = UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+    let data = Metadata::from(time);
+    assert_eq!(data, Metadata::Timestamp(1_700_000_000_000));
+    assert_eq!(SystemTime::try_from(data).unwrap(), time);
+
+    let time = UNIX_EPOCH - Duration::from_millis(1000);
+    assert_eq!(Metadata::from(time), Metadata::Timestamp(-1000));
+}
+
+#[test]
+fn legacy_metadata_compatibility() {
+    // The metadata enum before the new variants were added.
+    #[derive(Serialize, Deserialize)]
+    enum LegacyMetadata {
+        Text(String),
+        Integer(usize),
+        Float(f32),
+        Array(Vec<LegacyMetadata>),
+        Object(HashMap<String, LegacyMetadata>),
+    }
+
+    let items = vec![LegacyMetadata::Integer(1), LegacyMetadata::Float(0.5)];
+    let legacy = LegacyMetadata::Object(HashMap::from([
+        ("title".to_string(), LegacyMetadata::Text("Hello".into())),
+        ("items".to_string(), LegacyMetadata::Array(items)),
+    ]));
+
+    let bytes = bincode::serialize(&legacy).unwrap();
+    let data: Metadata = bincode::deserialize(&bytes).unwrap();
+
+    assert_eq!(data.get("title").unwrap().as_str(), Some("Hello"));
+    assert_eq!(data.get("items.0"), Some(&Metadata::Integer(1)));
+    assert_eq!(data.get("items.1"), Some(&Metadata::Float(0.5)));
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Status {
+    Draft,
+    Published { views: u32 },
+    Archived(i64),
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Article {
+    title: String,
+    rating: f32,
+    tags: Vec<String>,
+    author: Option<String>,
+    status: Vec<Status>,
+    scores: HashMap<u32, f64>,
+}
+
+#[test]
+fn serializable_round_trip() {
+    let article = Article {
+        title: "Vector search".into(),
+        rating: 4.5,
+        tags: vec!["hnsw".into(), "ann".into()],
+        author: None,
+        status: vec![
+            Status::Draft,
+            Status::Published { views: 10 },
+            Status::Archived(-1),
+        ],
+        scores: HashMap::from([(1, 0.5)]),
+    };
+
+    let data = Metadata::from_serializable(&article).unwrap();
+    assert_eq!(data.get("title").unwrap().as_str(), Some("Vector search"));
+    assert_eq!(data.get("author"), Some(&Metadata::Null));
+    assert_eq!(data.get("status.0").unwrap().as_str(), Some("Draft"));
+    assert_eq!(
+        data.get("status.1.Published.views").unwrap().as_usize(),
+        Some(10)
+    );
+    assert_eq!(data.get("scores.1"), Some(&Metadata::Double(0.5)));
+
+    let result: Article = data.deserialize_into().unwrap();
+    assert_eq!(result, article);
+}
+
+#[test]
+fn deserialize_into_invalid_type() {
+    let data = Metadata::from(HashMap::from([("title", 5)]));
+    assert!(data.deserialize_into::<Article>().is_err());
+}
+
+#[test]
+fn json_value_conversion() {
+    let value = json!({
+        "title": "Hello",
+        "count": 3,
+        "offset": -2,
+        "score": 0.25,
+        "tags": ["a", null, true],
+    });
+
+    let data = Metadata::from(value.clone());
+    assert_eq!(data.get("count"), Some(&Metadata::Integer(3)));
+    assert_eq!(data.get("offset"), Some(&Metadata::Signed(-2)));
+    assert_eq!(data.get("score"), Some(&Metadata::Double(0.25)));
+    assert_eq!(data.get("tags.2").unwrap().as_bool(), Some(true));
+
+    assert_eq!(serde_json::Value::from(data), value);
+}
+
+#[test]
+fn merge_patch() {
+    let mut data = Metadata::from(serde_json::json!({
+        "title": "Hello",
+        "author": { "name": "Elijah", "age": 30 },
+        "tags": ["a", "b"],
+    }));
+
+    let patch = Metadata::from(serde_json::json!({
+        "author": { "age": null, "email": "e@x.com" },
+        "tags": ["c"],
+        "draft": false,
+    }));
+
+    data.merge(&patch);
+
+    let expected = Metadata::from(serde_json::json!({
+        "title": "Hello",
+        "author": { "name": "Elijah", "email": "e@x.com" },
+        "tags": ["c"],
+        "draft": false,
+    }));
+
+    assert_eq!(data, expected);
+
+    // Patches that aren't objects replace the metadata.
+    data.merge(&Metadata::from(5usize));
+    assert_eq!(data, Metadata::Integer(5));
+}
@@ -0,0 +1,102 @@
+use super::*;
+use crate::metadata::*;
+use crate::schema::*;
+
+const DIMENSION: usize = 32;
+
+fn create_schema() -> Schema {
+    let author = Schema::new()
+        .required("name", FieldType::Text)
+        .optional("age", FieldType::Integer);
+
+    Schema::new()
+        .required("title", FieldType::Text)
+        .required("author", FieldType::Object(author))
+        .optional("score", FieldType::Float)
+        .optional("tags", FieldType::Array(Box::new(FieldType::Text)))
+}
+
+fn create_data(title: Metadata, author: Metadata) -> Metadata {
+    HashMap::from([("title", title), ("author", author)]).into()
+}
+
+fn author(name: &str) -> Metadata {
+    HashMap::from([("name", name)]).into()
+}
+
+fn validation_error(error: Box<dyn std::error::Error>) -> ValidationError {
+    error.downcast_ref::<ValidationError>().unwrap().clone()
+}
+
+#[test]
+fn validate_valid_metadata() {
+    let schema = create_schema();
+    let mut data = create_data("Hello".into(), author("Elijah"));
+    assert!(schema.validate(&data).is_ok());
+
+    // Optional fields can be null and floats accept integers.
+    if let Metadata::Object(obj) = &mut data {
+        obj.insert("score".into(), 5.into());
+        obj.insert("tags".into(), Metadata::Null);
+    }
+
+    assert!(schema.validate(&data).is_ok());
+}
+
+#[test]
+fn validate_invalid_metadata() {
+    let schema = create_schema();
+
+    let data = create_data(Metadata::Null, author("Elijah"));
+    let error = schema.validate(&data).unwrap_err();
+    assert_eq!(error.path, "title");
+    assert_eq!(error.kind, ValidationErrorKind::Missing);
+
+    let data = create_data("Hello".into(), author("Elijah"));
+    let data = match data {
+        Metadata::Object(mut obj) => {
+            obj.insert(
+                "tags".into(),
+                vec![Metadata::from("a"), 1.into()].into(),
+            );
+            Metadata::Object(obj)
+        }
+        _ => unreachable!(),
+    };
+
+    let error = schema.validate(&data).unwrap_err();
+    assert_eq!(error.path, "tags.1");
+    assert_eq!(error.to_string(), "The field `tags.1` must be of type Text.");
+
+    let author = HashMap::from([("name", "Elijah"), ("email", "x")]);
+    let data = create_data("Hello".into(), author.into());
+    let error = schema.validate(&data).unwrap_err();
+    assert_eq!(error.path, "author.email");
+    assert_eq!(error.kind, ValidationErrorKind::Unknown);
+
+    let error = schema.validate(&"Hello".into()).unwrap_err();
+    assert_eq!(error.path, "");
+}
+
+#[test]
+fn collection_validates_records() {
+    let config = Config { schema: Some(create_schema()), ..Default::default() };
+    let valid = create_data("Hello".into(), author("Elijah"));
+    let invalid = create_data(5.into(), author("Elijah"));
+
+    let vector = Vector::random(DIMENSION);
+    let records =
+        vec![Record::new(&vector, &valid), Record::new(&vector, &invalid)];
+    let error = Collection::build(&config, &records).err().unwrap();
+    assert_eq!(validation_error(error).path, "title");
+
+    let mut collection = Collection::build(&config, &records[..1]).unwrap();
+    assert!(collection.insert(&records[1]).is_err());
+    assert!(collection.update(&VectorID(0), &records[1]).is_err());
+    assert_eq!(collection.len(), 1);
+    assert_eq!(collection.get(&VectorID(0)).unwrap().data, valid);
+
+    let collection = ConcurrentCollection::new(&config);
+    assert!(collection.insert(&records[1]).is_err());
+    assert!(collection.insert(&records[0]).is_ok());
+}
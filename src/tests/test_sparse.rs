@@ -0,0 +1,93 @@
+use super::*;
+
+fn create_sparse_collection() -> Collection {
+    let sparse = [
+        vec![(1, 1.0), (5, 2.0)],
+        vec![(1, 0.5), (7, 1.0)],
+        vec![(3, 1.0)],
+        vec![(5, 1.0), (7, 3.0)],
+    ];
+
+    let records: Vec<Record> = sparse
+        .into_iter()
+        .map(|pairs| Record::random(128).with_sparse(&pairs.into()))
+        .collect();
+
+    create_collection(&records)
+}
+
+#[test]
+fn sparse_vector_new() {
+    let vector = SparseVector::new(&[(7, 1.0), (2, 0.5), (7, 2.0)]);
+    assert_eq!(vector.pairs(), &[(2, 0.5), (7, 3.0)]);
+
+    let other = SparseVector::from(vec![(7, 2.0), (9, 1.0)]);
+    assert_eq!(vector.dot(&other), 6.0);
+}
+
+#[test]
+fn sparse_search() {
+    let collection = create_sparse_collection();
+    let query = SparseVector::new(&[(5, 1.0), (7, 1.0)]);
+
+    let result = collection.sparse_search(&query, 10).unwrap();
+    let ids: Vec<u32> = result.iter().map(|r| r.id).collect();
+    assert_eq!(ids, vec![3, 0, 1]);
+    assert_eq!(result[0].score, 4.0);
+}
+
+#[test]
+fn sparse_search_incremental() {
+    let mut collection = create_sparse_collection();
+    let query = SparseVector::new(&[(3, 1.0)]);
+
+    let record = Record::random(128).with_sparse(&vec![(3, 2.0)].into());
+    collection.insert(&record).unwrap();
+    collection.update(&VectorID(2), &Record::random(128)).unwrap();
+    collection.delete(&VectorID(0)).unwrap();
+
+    let result = collection.sparse_search(&query, 10).unwrap();
+    let ids: Vec<u32> = result.iter().map(|r| r.id).collect();
+    assert_eq!(ids, vec![4]);
+
+    let record = collection.get(&VectorID(4)).unwrap();
+    assert_eq!(record.sparse, Some(vec![(3, 2.0)].into()));
+    assert!(collection.get(&VectorID(2)).unwrap().sparse.is_none());
+}
+
+#[test]
+fn sparse_non_finite() {
+    let mut collection = create_sparse_collection();
+    let sparse = SparseVector::new(&[(4, f32::NAN)]);
+    let record = Record::random(128).with_sparse(&sparse);
+    assert!(collection.insert(&record).is_err());
+    assert!(collection.sparse_search(&sparse, 10).is_err());
+}
+
+#[test]
+fn hybrid_sparse_search() {
+    let collection = create_sparse_collection();
+    let vector = collection.get(&VectorID(1)).unwrap().vector;
+    let sparse = SparseVector::new(&[(7, 1.0)]);
+
+    // The record matching both of the vectors ranks first.
+    let fusion = Fusion::default();
+    let result = collection.hybrid_sparse_search(&vector, &sparse, 2, &fusion);
+    let result = result.unwrap();
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0].id, 1);
+}
+
+#[test]
+fn concurrent_sparse() {
+    let collection: ConcurrentCollection = create_sparse_collection().into();
+    let record = Record::random(128).with_sparse(&vec![(3, 2.0)].into());
+    collection.insert(&record).unwrap();
+
+    // The sparse vectors are kept through the conversions.
+    let collection = collection.to_collection();
+    let query = SparseVector::new(&[(3, 1.0)]);
+    let result = collection.sparse_search(&query, 10).unwrap();
+    let ids: Vec<u32> = result.iter().map(|r| r.id).collect();
+    assert_eq!(ids, vec![4, 2]);
+}
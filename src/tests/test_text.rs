@@ -0,0 +1,105 @@
+use super::*;
+
+fn create_text_collection() -> Collection {
+    let texts = [
+        "The quick brown fox jumps over the lazy dog.",
+        "A lazy afternoon with a good book.",
+        "Foxes are quick and clever animals.",
+        "Rust is a fast and safe systems language.",
+    ];
+
+    let records: Vec<Record> = texts
+        .iter()
+        .map(|text| HashMap::from([("content", *text)]).into())
+        .map(|data| Record::new(&Vector::random(128), &data))
+        .collect();
+
+    let config =
+        Config { text_field: Some("content".into()), ..Default::default() };
+    Collection::build(&config, &records).unwrap()
+}
+
+#[test]
+fn tokenize() {
+    let terms = text::tokenize("Hello, World! It's 2024.");
+    assert_eq!(terms, vec!["hello", "world", "it", "s", "2024"]);
+}
+
+#[test]
+fn text_search() {
+    let collection = create_text_collection();
+
+    let result = collection.text_search("QUICK fox", 10).unwrap();
+    let ids: Vec<u32> = result.iter().map(|r| r.id).collect();
+    assert_eq!(ids, vec![0, 2]);
+    assert!(result[0].score > result[1].score);
+
+    let result = collection.text_search("lazy", 1).unwrap();
+    assert_eq!(result.len(), 1);
+    assert!(collection.text_search("unknown", 10).unwrap().is_empty());
+}
+
+#[test]
+fn text_search_incremental() {
+    let mut collection = create_text_collection();
+
+    let data = HashMap::from([("content", "A database in Rust.")]).into();
+    collection.update_data(&VectorID(1), &data).unwrap();
+    collection.delete(&VectorID(3)).unwrap();
+    let record = Record::new(&Vector::random(128), &"Rust".into());
+    collection.insert(&record).unwrap();
+
+    // Records without the text field are not indexed.
+    let result = collection.text_search("rust", 10).unwrap();
+    let ids: Vec<u32> = result.iter().map(|r| r.id).collect();
+    assert_eq!(ids, vec![1]);
+    assert!(collection.text_search("book", 10).unwrap().is_empty());
+}
+
+#[test]
+fn text_search_not_configured() {
+    let collection = create_collection(&Record::many_random(128, 10));
+    assert!(collection.text_search("text", 10).is_err());
+}
+
+#[test]
+fn hybrid_search_reciprocal() {
+    let collection = create_text_collection();
+    let query = collection.get(&VectorID(2)).unwrap().vector;
+
+    // The record matching both the vector and the text ranks first.
+    let fusion = Fusion::default();
+    let result = collection.hybrid_search(&query, "quick", 4, &fusion).unwrap();
+    assert_eq!(result.len(), 4);
+    assert_eq!(result[0].id, 2);
+    assert!(result.windows(2).all(|w| w[0].score >= w[1].score));
+}
+
+#[test]
+fn hybrid_search_weighted() {
+    let collection = create_text_collection();
+    let query = collection.get(&VectorID(3)).unwrap().vector;
+
+    // The weight decides which of the searches wins.
+    let text_only = Fusion::Weighted(0.0);
+    let result = collection.hybrid_search(&query, "book", 1, &text_only);
+    assert_eq!(result.unwrap()[0].id, 1);
+
+    let vector_only = Fusion::Weighted(1.0);
+    let result = collection.hybrid_search(&query, "book", 1, &vector_only);
+    let result = result.unwrap();
+    assert_eq!(result[0].id, 3);
+    assert_eq!(result[0].score, 1.0);
+}
+
+#[test]
+fn hybrid_search_invalid_weight() {
+    let collection = create_text_collection();
+    let query = collection.get(&VectorID(3)).unwrap().vector;
+
+    for weight in [f32::NAN, f32::INFINITY, -0.1, 1.5] {
+        let fusion = Fusion::Weighted(weight);
+        let result = collection.hybrid_search(&query, "book", 1, &fusion);
+        assert!(result.is_err());
+    }
+}
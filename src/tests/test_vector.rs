@@ -0,0 +1,41 @@
+use super::*;
+
+/// Returns the Euclidean distance without the SIMD kernels.
+fn naive_distance(a: &Vector, b: &Vector) -> f32 {
+    let iter = a.0.iter().zip(b.0.iter());
+    iter.map(|(a, b)| (a - b).powi(2)).sum::<f32>().sqrt()
+}
+
+#[test]
+fn distance() {
+    // The dimensions cover the tails of each kernel width.
+    let dimensions = (0..=40).chain([128, 384, 1536]);
+    for dimension in dimensions {
+        let a = Vector::random(dimension);
+        let b = Vector::random(dimension);
+        let expected = naive_distance(&a, &b);
+        let distance = a.distance(&b);
+        assert!((distance - expected).abs() <= expected * 1e-5 + 1e-6);
+    }
+
+    let vector = Vector::random(128);
+    assert_eq!(vector.distance(&vector), 0.0);
+}
+
+#[test]
+fn search_distance() {
+    let records = Record::many_random(128, 100);
+    let collection = create_collection(&records);
+
+    // The search compares the squared distances but returns the
+    // Euclidean distances.
+    let query = Vector::random(128);
+    let result = collection.search(&query, 5).unwrap();
+    for item in result.iter() {
+        let vector = &records[item.id as usize].vector;
+        assert_eq!(item.distance, query.distance(vector));
+    }
+
+    let truth = collection.true_search(&query, 1).unwrap();
+    assert_eq!(truth[0].distance, result[0].distance);
+}